@@ -1,11 +1,117 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+//Contrato externo que da fe de que un dni (hasheado con Blake2x256 sobre su codificacion SCALE) pertenece a una cuenta
+#[ink::trait_definition]
+pub trait OraculoIdentidad {
+    #[ink(message)]
+    fn verificar_dni(&self, dni_hash:[u8; 32], cuenta:ink::primitives::AccountId) -> bool;
+}
+
+//Extension de PSP22 con saldos historicos (como balanceOfAt de los tokens con instantaneas), para ponderar los votos por tenencia de tokens.
+//No es parte del estandar: un token PSP22 comun no la implementa y no se puede usar en una votacion por token. Devuelve el saldo que tenia la cuenta en el momento dado (timestamp en milisegundos)
+#[ink::trait_definition]
+pub trait PSP22Historico {
+    #[ink(message)]
+    fn balance_of_at(&self, owner:ink::primitives::AccountId, momento:u64) -> u128;
+}
+
+//Identificador de un NFT segun el estandar PSP34
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum Id {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bytes(ink::prelude::vec::Vec<u8>),
+}
+
+//Parte del estandar PSP34 que se usa para habilitar a votar a quien tenga un NFT de una coleccion
+#[ink::trait_definition]
+pub trait PSP34 {
+    #[ink(message)]
+    fn owner_of(&self, id:Id) -> Option<ink::primitives::AccountId>;
+}
+
 #[ink::contract]
 mod sistema {
     use ink::prelude::vec::Vec;
     use ink::prelude::string::String;
-    use ink::prelude::collections::BTreeMap;
+    use ink::prelude::collections::{BTreeMap, BTreeSet};
+    use ink::codegen::TraitCallBuilder;
+    use ink::env::hash::Blake2x256;
+    use crate::OraculoIdentidad;
+    use crate::PSP22Historico;
+    use crate::{PSP34, Id};
+    use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+    use curve25519_dalek::scalar::Scalar;
+    use curve25519_dalek::traits::Identity;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    const EDAD_MINIMA_REGISTRO: u32 = 16;  // ninguna votacion puede pedir una edad menor a esta
+    const ESPERA_REINTENTO: Timestamp = 7 * 24 * 60 * 60 * 1000;  // una semana entre un rechazo y la siguiente solicitud
+    const MAX_RECHAZOS: usize = 3;  // despues de tantos rechazos no se puede volver a solicitar
+    const ESCALA_PONDERACION: u64 = 10_000;  // el conteo ponderado se guarda en diezmilesimas para no perder los decimales
+    const DIA: Timestamp = 24 * 60 * 60 * 1000;
+    const ANILLO_MINIMO: usize = 16;  // un voto anonimo se esconde entre al menos tantas claves (o todas las registradas, si son menos)
+
+    //Dos Blake2x256 de la codificacion SCALE de (mitad, datos), para llevar un hash a un escalar o a un punto sin sesgo
+    fn hash_ancho<T: scale::Encode>(datos:&T)->[u8; 64]{
+        let mut salida = [0u8; 64];
+        salida.chunks_mut(32).enumerate().for_each(|(mitad, parte)| {
+            let mut h = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&(mitad as u8, datos), &mut h);
+            parte.copy_from_slice(&h);
+        });
+        salida
+    }
+
+    //Punto de Ristretto comprimido, distinto del neutro
+    fn punto_de(bytes:[u8; 32])->Option<RistrettoPoint>{
+        CompressedRistretto(bytes).decompress().filter(|p| *p != RistrettoPoint::identity())
+    }
+
+    fn escalar_de(bytes:[u8; 32])->Option<Scalar>{
+        Option::from(Scalar::from_canonical_bytes(bytes))
+    }
+
+    //La imagen de la clave se calcula sobre este punto, que depende solo de la clave publica, asi es la misma en cualquier anillo
+    fn hash_a_punto(clave:&[u8; 32])->RistrettoPoint{
+        RistrettoPoint::from_uniform_bytes(&hash_ancho(&(b"sistema/anillo/punto", clave)))
+    }
+
+    fn desafio_anillo(mensaje:[u8; 32], l:&RistrettoPoint, r:&RistrettoPoint)->Scalar{
+        Scalar::from_bytes_mod_order_wide(&hash_ancho(&(b"sistema/anillo/desafio", mensaje, l.compress().to_bytes(), r.compress().to_bytes())))
+    }
+
+    //Firma de anillo enlazable (bLSAG): con I = x*Hp(P) la imagen de la clave, para cada clave P_i del anillo c_{i+1} = H(mensaje, r_i*G + c_i*P_i, r_i*Hp(P_i) + c_i*I),
+    //y la firma vale si despues de la ultima clave se vuelve a c_0. Solo la puede armar el que conoce la clave privada de alguna P_i, y no dice cual
+    fn verificar_firma_anillo(mensaje:[u8; 32], firma:&FirmaAnillo)->bool{
+        if firma.anillo.is_empty() || firma.anillo.len() != firma.respuestas.len(){
+            return false
+        }
+        let (Some(imagen), Some(desafio)) = (punto_de(firma.imagen), escalar_de(firma.desafio)) else { return false };
+        let mut c = desafio;
+        for (clave, respuesta) in firma.anillo.iter().zip(firma.respuestas.iter()) {
+            let (Some(p), Some(r)) = (punto_de(*clave), escalar_de(*respuesta)) else { return false };
+            let l = RistrettoPoint::vartime_double_scalar_mul_basepoint(&c, &p, &r);
+            let rr = r * hash_a_punto(clave) + c * imagen;
+            c = desafio_anillo(mensaje, &l, &rr);
+        }
+        c == desafio
+    }
 
+    //Coeficiente de Lagrange del custodio i (indices empezando en 1) para interpolar en z, en el cuerpo de escalares de Ristretto
+    fn coeficiente_lagrange(indices:&[u64], i:usize, z:u64)->Scalar{
+        indices.iter().enumerate().filter(|(k, _)| *k != i).fold(Scalar::ONE, |coef, (_, xk)| {
+            coef * (Scalar::from(z) - Scalar::from(*xk)) * (Scalar::from(indices[i]) - Scalar::from(*xk)).invert()
+        })
+    }
 
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
     #[cfg_attr(
@@ -27,10 +133,13 @@ mod sistema {
     pub struct Usuario{
         nombre:String,
         apellido:String,
-        edad:i32,
+        fecha_nacimiento:Fecha,
         dni:i128,
         verificado:bool,
         rol:Option<Rol>,
+        categoria:Option<String>,  // la asigna el administrador (ej: "docente", "afiliado")
+        suspendido:bool,
+        fecha_alta:Timestamp,  // cuando quedo registrado (validado), para armar el padron de una votacion abierta a su fecha de inicio
         acc_id:AccountId
     }
     impl PartialEq for Usuario{
@@ -40,8 +149,13 @@ mod sistema {
     }
     impl Usuario{
 
-        pub fn new(nombre:String,apellido:String,dni:i128,edad:i32,verificado:bool,rol:Option<Rol>,acc_id:AccountId)->Self{
-            Self{nombre,apellido,dni,edad,verificado,rol,acc_id}
+        pub fn new(nombre:String,apellido:String,dni:i128,fecha_nacimiento:Fecha,verificado:bool,rol:Option<Rol>,acc_id:AccountId)->Self{
+            Self{nombre,apellido,dni,fecha_nacimiento,verificado,rol,categoria:None,suspendido:false,fecha_alta:0,acc_id}
+        }
+
+        //La edad se calcula en el momento del chequeo, no queda guardada
+        pub fn edad(&self, momento:Timestamp)->u32{
+            self.fecha_nacimiento.edad_en(momento)
         }
     }
 
@@ -50,7 +164,7 @@ mod sistema {
         feature = "std",
         derive(ink::storage::traits::StorageLayout)
     )]
-    #[derive(Debug)]
+    #[derive(Debug,Clone)]
     pub struct Fecha{
         pub dia:u32,
         pub mes:u32,
@@ -59,7 +173,20 @@ mod sistema {
     impl Fecha {
 
         fn es_fecha_valida(&self)->bool{
-            if (self.mes>0)&&(self.mes<=12)&&(self.dia>0)&&(self.dia<=31)&&(self.anio>=1970){
+            self.anio>=1970 && self.es_dia_valido()
+        }
+
+        //Las fechas de nacimiento pueden ser anteriores a 1970, pero no posteriores al momento actual
+        fn es_fecha_de_nacimiento_valida(&self, momento:Timestamp)->bool{
+            if self.anio<1900 || !self.es_dia_valido(){
+                return false
+            }
+            let hoy = Fecha::from_timestamp(momento);
+            (self.anio,self.mes,self.dia) <= (hoy.anio,hoy.mes,hoy.dia)
+        }
+
+        fn es_dia_valido(&self)->bool{
+            if (self.mes>0)&&(self.mes<=12)&&(self.dia>0)&&(self.dia<=31){
                 match self.mes {
                     2=> {
                         if self.is_leap_year(self.anio){
@@ -78,6 +205,44 @@ mod sistema {
             false
         }
 
+        //Convierte un timestamp (en milisegundos desde el Epoch) a la fecha de ese dia
+        pub fn from_timestamp(momento:Timestamp) -> Fecha {
+            let millis_per_day: u64 = 24 * 60 * 60 * 1000;
+            let mut dias = momento / millis_per_day;
+            let mut fecha = Fecha{ dia:1, mes:1, anio:1970 };
+
+            loop {
+                let dias_anio = if fecha.is_leap_year(fecha.anio) { 366 } else { 365 };
+                if dias < dias_anio {
+                    break;
+                }
+                dias = dias.wrapping_sub(dias_anio);
+                fecha.anio = fecha.anio.wrapping_add(1);
+            }
+
+            loop {
+                let dias_mes = fecha.days_in_month(fecha.mes) as u64;
+                if dias < dias_mes {
+                    break;
+                }
+                dias = dias.wrapping_sub(dias_mes);
+                fecha.mes = fecha.mes.wrapping_add(1);
+            }
+
+            fecha.dia = fecha.dia.wrapping_add(dias as u32);
+            fecha
+        }
+
+        //Años cumplidos en el momento dado (0 si la fecha todavia no llego)
+        pub fn edad_en(&self, momento:Timestamp) -> u32 {
+            let hoy = Fecha::from_timestamp(momento);
+            let mut edad = hoy.anio.saturating_sub(self.anio);
+            if (hoy.mes,hoy.dia) < (self.mes,self.dia) {
+                edad = edad.saturating_sub(1);
+            }
+            if edad < 0 { 0 } else { edad as u32 }
+        }
+
         pub fn to_timestamp(&self) -> Timestamp {
             let days_since_epoch = self.days_since_epoch() as i64;
             let millis_per_day: i64 = 24 * 60 * 60 * 1000;
@@ -132,214 +297,1892 @@ mod sistema {
         feature = "std",
         derive(ink::storage::traits::StorageLayout)
     )]
-    #[derive(Debug)]
-    struct Votacion{
-        id:i32,
-        puesto:String,
-        candidatos:Vec<AccountId>,
-        votantes: Vec<AccountId>,
-        votos: BTreeMap<AccountId,u32>,    // hashmap con accountid de candidato
-        votaron: Vec<AccountId>,
-        fecha_inicio:Timestamp,
-        fecha_fin:Timestamp,
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum AccionSuspension{
+        Suspension,
+        Rehabilitacion,
+        Baja,
     }
-    impl Votacion{
-        pub fn new(id:i32,puesto:String, fecha_inicio:Timestamp, fecha_fin:Timestamp)-> Votacion{
-            Votacion {
-                id, puesto, candidatos:Vec::new(),votantes:Vec::new(), votos:BTreeMap::new(), votaron:Vec::new(),fecha_inicio, fecha_fin
-            }
-        }
 
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct RegistroSuspension{
+        pub acc_id:AccountId,
+        pub dni:i128,
+        pub accion:AccionSuspension,
+        pub motivo:String,
+        pub momento:Timestamp,
+    }
 
-        pub fn get_cant_candidatos_vot(&self)->i32{
-            let x = self.candidatos.len();
-            x as i32
-        }
 
-        pub fn get_cant_votantes_vot(&self)->i32{
-            let x = self.votantes.len();
-            x as i32
-        }
-        
-        pub fn inicio(&self, momento:Timestamp)->bool{  // trabajar con fechas
-            momento > self.fecha_inicio
-        }
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum TipoSolicitud{
+        Registro,
+        CambioPerfil,
+    }
 
-        pub fn finalizo(&self, momento:Timestamp)->bool{ // trabajar con fechas
-            momento > self.fecha_fin
-        }
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct Rechazo{
+        pub acc_id:AccountId,
+        pub dni:i128,
+        pub tipo:TipoSolicitud,
+        pub motivo:String,
+        pub momento:Timestamp,
+    }
+
+    //Los campos en None no se modifican
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct SolicitudCambioPerfil{
+        pub acc_id:AccountId,
+        pub nombre:Option<String>,
+        pub apellido:Option<String>,
+    }
 
 
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum IdentificadorUsuario{
+        Cuenta(AccountId),
+        Dni(i128),
+    }
 
-        pub fn es_votante(&self, acc_id:AccountId)->bool{
-            return self.votantes.iter().any(|u| *u == acc_id)
-        }
+    //Por que no se pudo inscribir una cuenta en una carga masiva
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum ErrorInscripcion{
+        NoRegistrado,
+        Suspendido,
+        NoCumpleRequisitos,
+        YaInscripto,
+    }
 
-        pub fn es_candidato(&self, acc_id:AccountId)->bool{
-            return self.candidatos.iter().any(|u| *u == acc_id)
+    //Como se arma la lista de votantes de una votacion
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum ModoInscripcion{
+        Abierta,        // todo usuario validado que cumpla los requisitos puede votar, sin postularse
+        Postulacion,    // el usuario se postula como votante y el administrador lo valida
+        PadronCerrado,  // el administrador carga la lista de votantes
+    }
+
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct Requisitos{
+        pub edad_minima_candidato:u32,
+        pub edad_minima_votante:u32,
+        pub categorias:Option<Vec<String>>,  // si es None cualquier categoria (o ninguna) puede participar
+        pub candidatos_pueden_votar:bool,  // si es true un candidato tambien puede ser votante en la misma votacion
+    }
+    impl Requisitos{
+
+        fn son_validos(&self)->bool{
+            self.edad_minima_candidato >= EDAD_MINIMA_REGISTRO && self.edad_minima_votante >= EDAD_MINIMA_REGISTRO
         }
 
-        pub fn sumar_candidato(&mut self,accid:AccountId){
-            self.candidatos.push(accid);
-            self.votos.insert(accid, 0);
+        //La edad se calcula al momento dado (normalmente el inicio de la votacion)
+        pub fn cumple(&self, us:&Usuario, rol:&Rol, momento:Timestamp)->bool{
+            let edad_minima = match rol {
+                Rol::Candidato => self.edad_minima_candidato,
+                Rol::Votante => self.edad_minima_votante,
+            };
+            if us.edad(momento) < edad_minima{
+                return false
+            }
+            match &self.categorias {
+                Some(cats) => us.categoria.as_ref().is_some_and(|c| cats.contains(c)),
+                None => true,
+            }
         }
 
-        pub fn sumar_votante(&mut self,accid:AccountId){
-            self.votantes.push(accid);
+        //Dos votaciones habilitan a los mismos votantes si piden la misma edad y las mismas categorias
+        fn mismos_votantes(&self, otros:&Requisitos)->bool{
+            let ordenadas = |r:&Requisitos| r.categorias.clone().map(|mut c| { c.sort(); c.dedup(); c });
+            self.edad_minima_votante == otros.edad_minima_votante && ordenadas(self) == ordenadas(otros)
         }
+    }
+
+
+    //Como se vota y se cuenta en una votacion
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum TipoVotacion{
+        Mayoria,                   // cada votante elige un candidato y gana el mas votado
+        SegundaVueltaInstantanea,  // cada votante ordena a los candidatos y se eliminan de a uno hasta que alguno tenga mayoria absoluta
+        Aprobacion{ max_aprobados:u32 },  // cada votante aprueba hasta max_aprobados candidatos y cada uno suma un voto
+        Proporcional{ bancas:u32, metodo:MetodoReparto, umbral_porcentaje:u32 },  // cada candidato es una lista y las bancas se reparten entre las que superan el umbral
+        Schulze,  // cada votante ordena a los candidatos y gana el que tiene los caminos de preferencia mas fuertes contra todos
+        Puntaje{ creditos:u32, cuadratico:bool },  // cada votante reparte creditos en puntos a los candidatos; si es cuadratico, dar n puntos cuesta n*n creditos
+        Referendum(Consulta),  // no hay candidatos, se vota una de las opciones de la consulta
+    }
+    impl TipoVotacion{
 
-        pub fn sumar_voto(&mut self,pos:usize){
-            self.votos.entry(self.candidatos[pos]).and_modify(|c|* c = c.wrapping_add(1));
+        fn usa_ranking(&self)->bool{
+            matches!(self, TipoVotacion::SegundaVueltaInstantanea | TipoVotacion::Schulze)
         }
 
-        pub fn ver_votos(&self,pos:i32)->u32{
-            if let Some(x)=self.votos.get(&self.candidatos[pos as usize]){
-                return *x
+        fn es_valido(&self)->bool{
+            match self {
+                TipoVotacion::Aprobacion{ max_aprobados } => *max_aprobados > 0,
+                TipoVotacion::Proporcional{ bancas, umbral_porcentaje, .. } => *bancas > 0 && *umbral_porcentaje <= 100,
+                TipoVotacion::Puntaje{ creditos, .. } => *creditos > 0,
+                TipoVotacion::Referendum(consulta) => consulta.es_valida(),
+                _ => true,
             }
-            0
         }
+    }
 
+    //Pregunta de un referendum. La primera opcion es la que se aprueba (ej: "Si") y si hay abstencion es la ultima opcion, que no cuenta para la base
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub struct Consulta{
+        pub pregunta:String,
+        pub opciones:Vec<String>,  // ej: ["Si", "No", "Abstencion"]
+        pub con_abstencion:bool,
+        pub mayoria:Mayoria,
+    }
+    impl Consulta{
+
+        fn es_valida(&self)->bool{
+            let minimo = if self.con_abstencion { 3 } else { 2 };
+            let porcentaje_valido = match self.mayoria {
+                Mayoria::Calificada{ porcentaje } => porcentaje > 50 && porcentaje <= 100,
+                Mayoria::Simple => true,
+            };
+            self.opciones.len() >= minimo && self.opciones.len() <= u8::MAX as usize && porcentaje_valido
+        }
 
+        //La base son todos los votos menos las abstenciones
+        fn esta_aprobada(&self, votos:&[u32])->bool{
+            let total: u64 = votos.iter().map(|v| *v as u64).sum();
+            let abstenciones = if self.con_abstencion { votos.last().copied().unwrap_or(0) as u64 } else { 0 };
+            let base = total.saturating_sub(abstenciones);
+            let a_favor = votos.first().copied().unwrap_or(0) as u64;
+            if base == 0 {
+                return false
+            }
+            match self.mayoria {
+                Mayoria::Simple => a_favor.saturating_mul(2) > base,
+                Mayoria::Calificada{ porcentaje } => a_favor.saturating_mul(100) >= base.saturating_mul(porcentaje as u64),
+            }
+        }
     }
 
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum Mayoria{
+        Simple,                        // mas de la mitad de la base
+        Calificada{ porcentaje:u32 },  // al menos ese porcentaje de la base (ej: 66 para dos tercios)
+    }
 
-    
-    #[ink(storage)]
-    pub struct Sistema {
-        nombre_administrador:String,
-        espera_usuarios: Vec<Usuario>,
-        usuarios_reg: Vec<Usuario>, // hashmap con account id
-        espera_candidatos:Vec<(AccountId,i32)>,
-        espera_votantes:Vec<(AccountId,i32)>,
-        votaciones:Vec<Votacion>,  // hashmap con id de votacion
-        admin:AccountId,
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone,PartialEq)]
+    pub enum MetodoReparto{
+        DHondt,       // divisores 1, 2, 3, ...
+        SainteLague,  // divisores 1, 3, 5, ...
     }
-    
+    impl MetodoReparto{
 
-    impl Sistema {
-        
-        //Constructor que recibe unicamente el nombre del administrador
-        #[ink(constructor)]
-        pub fn new(nombre_administrador: String) -> Self {
-            Self { nombre_administrador,espera_usuarios:Vec::new(),espera_candidatos:Vec::new(),espera_votantes:Vec::new(), usuarios_reg:Vec::new(),votaciones: Vec::new(), admin: Self::env().caller() }
+        fn divisor(&self, bancas_asignadas:u32)->u32{
+            match self {
+                MetodoReparto::DHondt => bancas_asignadas.saturating_add(1),
+                MetodoReparto::SainteLague => bancas_asignadas.saturating_mul(2).saturating_add(1),
+            }
         }
+    }
+
+    //Lo que manda el votante, tiene que corresponder con el tipo de la votacion
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub enum Boleta{
+        Simple(i32),               // numero de opcion, empezando en 1
+        Ranking(Vec<AccountId>),   // candidatos de mayor a menor preferencia, puede ser parcial
+        Aprobacion(Vec<AccountId>),  // candidatos aprobados, los repetidos se cuentan una sola vez
+        Puntajes(Vec<(AccountId,u32)>),  // puntos que se le dan a cada candidato
+    }
 
+    //Prueba de que el voto anonimo lo manda el dueño de alguna de las claves registradas, sin decir cual
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct FirmaAnillo{
+        pub anillo:Vec<[u8; 32]>,      // claves registradas entre las que se esconde el votante, sin repetir
+        pub imagen:[u8; 32],           // imagen de la clave, clave_privada * Hp(clave_publica): es el anulador del voto
+        pub desafio:[u8; 32],          // c_0, escalar en 32 bytes little endian
+        pub respuestas:Vec<[u8; 32]>,  // r_i, una por cada clave del anillo
+    }
 
-        //Crea un usuario verificando que no sea el administrador y que no este repetido y lo agrega a la lista de espera de aprobacion del administrador
-        #[ink(message)]
-        pub fn registrar_usuario(&mut self, nom:String,apellido:String,edad:i32, dni:i128) {
-            self.registrar_usuario_impl(nom, apellido, edad, dni);
-        }
+    //Votaciones que se votan juntas con un mismo padron. La primera es la principal, y las demas usan su padron
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct GrupoBoletas{
+        pub id:i32,
+        pub votaciones:Vec<i32>,
+    }
 
-        fn registrar_usuario_impl(&mut self, nom:String,apellido:String,edad:i32, dni:i128) {
-            let caller = self.env().caller();
-            if caller != self.admin {  //el administrador no se puede registrar como un usuario 
-                let aux: Usuario = Usuario::new(nom, apellido, dni, edad, false, None, caller);
-                if edad >= 18 {
-                    if  !self.usuarios_reg.iter().any(|u| u.dni == dni || u.acc_id == caller) {  // no puede haber dos usuarios con el mismo dni 
-                        if !self.espera_usuarios.contains(&aux){
-                            self.espera_usuarios.push(aux);
-                        }else{
-                            panic!("ESTE USUARIO YA ESTA EN ESPERA DE VALIDACION");
-                        }
-                        
-                    }else{
-                        panic!("CUENTA O DNI YA REGISTRADO");
-                    }
-                }
+    //Todo lo que se elige al crear una votacion, ademas del id, el puesto y las fechas
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct ConfiguracionVotacion{
+        pub requisitos:Requisitos,
+        pub modo_inscripcion:ModoInscripcion,
+        pub tipo:TipoVotacion,
+        pub claustros:Option<Vec<Claustro>>,  // si es None todos los votos valen lo mismo
+        pub token:Option<AccountId>,  // contrato PSP22Historico: si esta, cada voto pesa el saldo que tenia el votante en la fecha de inicio
+        pub coleccion_nft:Option<AccountId>,  // contrato PSP34: si esta, para votar hay que presentar un NFT propio de la coleccion
+        pub dias_revelacion:Option<u32>,  // si esta, el voto es secreto: se vota con un compromiso y se revela en los dias siguientes a la fecha de fin
+        pub anonima:bool,  // si es true los votantes registran una clave publica antes del inicio y votan desde cualquier cuenta con una firma de anillo
+        pub custodia:Option<Custodia>,  // si esta, las boletas se guardan cifradas y solo se pueden abrir con las partes de la clave de los custodios
+    }
+    impl ConfiguracionVotacion{
+
+        //La ponderacion solo se puede usar en votaciones que suman votos o puntos por candidato, y cada claustro tiene que tener una categoria distinta y peso.
+        //Solo pueden votar las categorias de los claustros, asi ningun voto queda afuera de la ponderacion
+        fn claustros_validos(&self)->bool{
+            match &self.claustros {
+                None => true,
+                Some(claustros) => {
+                    let mut categorias: Vec<String> = claustros.iter().map(|c| c.categoria.clone()).collect();
+                    categorias.sort();
+                    matches!(self.tipo, TipoVotacion::Mayoria | TipoVotacion::Aprobacion{ .. } | TipoVotacion::Puntaje{ .. })
+                    && !claustros.is_empty()
+                    && claustros.iter().all(|c| c.peso > 0)
+                    && claustros.iter().enumerate().all(|(i, c)| !claustros[..i].iter().any(|o| o.categoria == c.categoria))
+                    && self.requisitos.categorias.clone().map(|mut c| { c.sort(); c.dedup(); c }) == Some(categorias)
+                },
             }
         }
 
-
-        //Unicamente el administrador puede validar o rechazar un usuario que solicito registrarse
-        #[ink(message)] 
-        pub fn validar_usuario(&mut self, aceptar: bool) {
-            self.validar_usuario_impl(aceptar);
+        //Los votos por token se suman por candidato, asi que no sirven para rankings, puntajes ni referendums, y no se combinan con claustros
+        fn token_valido(&self)->bool{
+            self.token.is_none() || (self.claustros.is_none() && matches!(self.tipo, TipoVotacion::Mayoria | TipoVotacion::Aprobacion{ .. } | TipoVotacion::Proporcional{ .. }))
         }
-    
-        fn validar_usuario_impl(&mut self, aceptar:bool){
-            let mut aux: Option<String> = None;
-            let caller = self.env().caller();
-            if caller == self.admin {  // solo el administrador puede validar candidatos 
-                if !self.espera_usuarios.is_empty() {  // checkea si hay candidatos a validar, y si hay se empieza a trabajar el primero
-                    let us = self.espera_usuarios[0].clone();
-                    let mut s1 = us.nombre.clone();
-                    let s2 = String::from(" ");
-                    let s3 = us.apellido.clone();
-                    s1.push_str(&s2);
-                    s1.push_str(&s3);
-                    aux = Some(s1);   
-                    if aceptar{  // el admin decide si aceptar o rechazar el candidato
-                        self.usuarios_reg.push(us)
-                    }
-                    self.espera_usuarios.remove(0);  // se elimina de la cola de espera de aprobacion 
-                }
-            }
-            if let Some(a) =aux{
-                ink::env::debug_println!("Aceptar solicitud de registro del usuario  {:?}",a);
-            } else{
-                ink::env::debug_println!("No hay solicitudes de registro");
-            }
+
+        //Con NFT el que habilita es tener el NFT, asi que la votacion tiene que ser abierta
+        fn nft_valido(&self)->bool{
+            self.coleccion_nft.is_none() || self.modo_inscripcion == ModoInscripcion::Abierta
         }
 
+        //Con NFT el voto se registra en el momento, asi que no puede ser secreto
+        fn secreto_valido(&self)->bool{
+            self.dias_revelacion.is_none_or(|dias| dias > 0 && self.coleccion_nft.is_none())
+        }
 
-        //Unicamente el administrador puede crear una votacion. No puede haber dos votaciones con el mismo id y las fechas de inicio y fin deben ser validas        #[ink(message)]
-        #[ink(message)]
-        pub fn crear_votacion(&mut self, id:i32, puesto:String, inicio:Fecha, fin:Fecha) {
-            self.crear_votacion_impl(id, puesto, inicio, fin);
+        //En una votacion anonima no se sabe que cuenta vota, asi que no se puede ponderar por claustro, token ni NFT
+        fn anonima_valida(&self)->bool{
+            !self.anonima || (self.claustros.is_none() && self.token.is_none() && self.coleccion_nft.is_none() && self.dias_revelacion.is_none())
         }
 
-        fn crear_votacion_impl(&mut self, id:i32, puesto:String,fecha_inicio:Fecha,fecha_fin:Fecha){ 
-            let caller = self.env().caller();
-            if !fecha_inicio.es_fecha_valida() | !fecha_fin.es_fecha_valida(){
+        //Una boleta cifrada es un numero de opcion, asi que solo sirve para votaciones de boleta simple, y se descifra sin saber quien la mando
+        fn custodia_valida(&self)->bool{
+            match &self.custodia {
+                None => true,
+                Some(c) => {
+                    c.umbral > 0 && c.umbral as usize <= c.custodios.len() && c.custodios.len() <= 255
+                    && c.custodios.iter().enumerate().all(|(i, acc)| !c.custodios[..i].contains(acc))
+                    && matches!(self.tipo, TipoVotacion::Mayoria | TipoVotacion::Proporcional{ .. } | TipoVotacion::Referendum(_))
+                    && self.claustros.is_none() && self.token.is_none() && self.coleccion_nft.is_none() && self.dias_revelacion.is_none() && !self.anonima
+                },
+            }
+        }
+    }
+
+    //Custodios de la clave de una votacion cifrada. Entre cualquier umbral de ellos pueden reconstruir la clave (reparto de Shamir)
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct Custodia{
+        pub custodios:Vec<AccountId>,  // el custodio i tiene la parte f(i), con i empezando en 1
+        pub umbral:u32,
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct Descifrado{
+        pub custodios:Vec<AccountId>,  // todos los que publicaron una parte verificada (la clave se reconstruye con las primeras umbral)
+        pub nulas:u32,                 // boletas que al descifrarse no eran una opcion valida
+    }
+
+    //En elecciones universitarias los votantes se agrupan por claustro (docentes, estudiantes, graduados, no docentes) segun su categoria, y cada claustro pesa lo mismo sin importar cuantos voten
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct Claustro{
+        pub categoria:String,
+        pub peso:u32,
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct ConteoClaustro{
+        pub categoria:String,
+        pub peso:u32,
+        pub votaron:u32,
+        pub conteo:Vec<(AccountId,u32)>,  // votos de cada candidato dentro del claustro, de mayor a menor
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct ConteoPonderado{
+        pub claustros:Vec<ConteoClaustro>,
+        pub conteo:Vec<(AccountId,u64)>,  // suma de peso * votos / votantes de cada claustro, en diezmilesimas, de mayor a menor
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct RondaEliminacion{
+        pub conteo:Vec<(AccountId,u32)>,           // votos de cada candidato que sigue en carrera
+        pub agotadas:u32,                          // boletas que ya no tienen ningun candidato en carrera
+        pub eliminado:Option<AccountId>,           // None en la ultima ronda
+        pub transferencias:Vec<(AccountId,u32)>,   // a quien pasan los votos del eliminado en la ronda siguiente
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub enum DetalleResultado{
+        Mayoria,
+        SegundaVueltaInstantanea(Vec<RondaEliminacion>),
+        Aprobacion,
+        Proporcional{
+            bancas:Vec<(AccountId,u32)>,          // bancas de cada lista, de mayor a menor
+            cocientes:Vec<(AccountId,Vec<u128>)>,  // votos/divisor de cada lista que supero el umbral, en milesimas de voto
+        },
+        Puntaje,
+        Referendum{
+            pregunta:String,
+            conteo:Vec<(String,u32)>,  // votos de cada opcion, en el orden de la consulta
+            aprobado:bool,
+        },
+        Schulze{
+            candidatos:Vec<AccountId>,         // orden de las filas y columnas de las matrices
+            preferencias:Vec<Vec<u32>>,        // [i][j]: cuantos votantes prefieren a i antes que a j
+            caminos:Vec<Vec<u32>>,             // [i][j]: fuerza del camino mas fuerte de i a j
+            ganador_condorcet:Option<AccountId>,  // el que le gana de a pares a todos los demas, si existe
+        },
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    pub struct Resultado{
+        pub ganador:Option<AccountId>,     // None si hubo empate o nadie voto
+        pub conteo:Vec<(AccountId,u128)>,  // de mayor a menor (en votaciones con ranking, primeras preferencias; por puntaje, puntos totales; por token, saldos sumados)
+        pub detalle:DetalleResultado,
+        pub ponderacion:Option<ConteoPonderado>,  // si la votacion pondera por claustros, el ganador sale de este conteo
+        pub sin_revelar:Vec<AccountId>,  // en una votacion secreta, los que votaron y no revelaron a tiempo (no cuentan en el conteo)
+        pub descifrado:Option<Descifrado>,  // en una votacion cifrada, que custodios abrieron las boletas
+    }
+
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Debug,Clone)]
+    struct Votacion{
+        id:i32,
+        puesto:String,
+        requisitos:Requisitos,
+        modo_inscripcion:ModoInscripcion,
+        candidatos:Vec<AccountId>,
+        votantes: Vec<AccountId>,
+        votos: BTreeMap<AccountId,u128>,    // hashmap con accountid de candidato
+        votaron: Vec<AccountId>,
+        padron: Option<[u8; 32]>,  // raiz de merkle de los hashes de dni habilitados para votar
+        hash_padron_congelado: Option<[u8; 32]>,  // hash de los dni de la lista de votantes al momento de iniciar, para auditoria
+        tipo:TipoVotacion,
+        boletas_ranking: Vec<Vec<u8>>,  // posiciones de los candidatos en orden de preferencia
+        votos_opciones: Vec<u32>,  // en un referendum, votos de cada opcion
+        resultado: Option<Resultado>,
+        claustros: Option<Vec<Claustro>>,
+        votos_claustro: Vec<(u32, BTreeMap<AccountId,u32>)>,  // por cada claustro, cuantos votaron y los votos de cada candidato
+        token: Option<AccountId>,
+        saldos: BTreeMap<AccountId,u128>,  // saldo de cada votante en la fecha de inicio, se consulta cuando vota
+        titulares_saldo: BTreeMap<AccountId,AccountId>,  // si un votante migro de cuenta antes de votar, la cuenta cuyo saldo le corresponde
+        coleccion_nft: Option<AccountId>,
+        nfts_usados: Vec<Id>,  // cada NFT vota una sola vez, aunque cambie de dueño
+        dias_revelacion: Option<u32>,
+        compromisos: BTreeMap<AccountId,([u8; 32],AccountId)>,  // en una votacion secreta, compromiso de cada votante y la cuenta con la que voto (si despues migra, el hash sigue atado a esa)
+        revelados: Vec<AccountId>,
+        anonima: bool,
+        claves_anonimas: BTreeMap<AccountId,[u8; 32]>,  // clave publica (punto de Ristretto comprimido) que registro cada votante antes del inicio
+        anuladores: Vec<[u8; 32]>,  // imagenes de clave de los votos anonimos
+        custodia: Option<Custodia>,
+        compromisos_custodios: Vec<Option<[u8; 32]>>,  // parte*G de cada custodio, cargado antes del inicio
+        clave_publica: Option<[u8; 32]>,  // clave*G, se calcula cuando todos los compromisos son consistentes
+        partes_clave: Vec<Option<[u8; 32]>>,  // publicadas despues de la fecha de fin
+        boletas_cifradas: Vec<([u8; 32],[u8; 32])>,  // (r*G, opcion*G + r*clave_publica)
+        padron_de: Option<i32>,  // id de la votacion cuyo padron se usa, si esta en un grupo de boletas y no es la principal
+        fecha_inicio:Timestamp,
+        fecha_fin:Timestamp,
+    }
+    impl Votacion{
+        pub fn new(id:i32,puesto:String,config:ConfiguracionVotacion, fecha_inicio:Timestamp, fecha_fin:Timestamp)-> Votacion{
+            let ConfiguracionVotacion{ requisitos, modo_inscripcion, tipo, claustros, token, coleccion_nft, dias_revelacion, anonima, custodia } = config;
+            let cant_custodios = custodia.as_ref().map(|c| c.custodios.len()).unwrap_or(0);
+            Votacion {
+                id, puesto, requisitos, modo_inscripcion, candidatos:Vec::new(),votantes:Vec::new(), votos:BTreeMap::new(), votaron:Vec::new(), padron:None, hash_padron_congelado:None,
+                votos_opciones: match &tipo { TipoVotacion::Referendum(consulta) => consulta.opciones.iter().map(|_| 0).collect(), _ => Vec::new() },
+                votos_claustro: claustros.iter().flatten().map(|_| (0, BTreeMap::new())).collect(),
+                tipo, claustros, token, saldos:BTreeMap::new(), titulares_saldo:BTreeMap::new(), coleccion_nft, nfts_usados:Vec::new(), dias_revelacion, compromisos:BTreeMap::new(), revelados:Vec::new(),
+                anonima, claves_anonimas:BTreeMap::new(), anuladores:Vec::new(),
+                custodia, compromisos_custodios:(0..cant_custodios).map(|_| None).collect(), clave_publica:None, partes_clave:(0..cant_custodios).map(|_| None).collect(), boletas_cifradas:Vec::new(), boletas_ranking:Vec::new(), resultado:None, padron_de:None, fecha_inicio, fecha_fin
+            }
+        }
+
+        //Recorre el camino de merkle desde la hoja (hash del dni) hasta la raiz. Cada par de nodos se ordena antes de hashearlo, asi la prueba no necesita indicar de que lado va cada hermano
+        pub fn esta_en_padron(&self, hoja:[u8; 32], prueba:&[[u8; 32]])->bool{
+            if let Some(raiz) = self.padron{
+                return Self::raiz_desde(hoja, prueba) == raiz
+            }
+            false
+        }
+
+        fn raiz_desde(hoja:[u8; 32], prueba:&[[u8; 32]])->[u8; 32]{
+            prueba.iter().fold(hoja, |actual, hermano| Self::hash_par(actual, *hermano))
+        }
+
+        fn hash_par(a:[u8; 32], b:[u8; 32])->[u8; 32]{
+            let mut par = [0u8; 64];
+            let (menor, mayor) = if a <= b { (a, b) } else { (b, a) };
+            par[..32].copy_from_slice(&menor);
+            par[32..].copy_from_slice(&mayor);
+            let mut salida = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&par, &mut salida);
+            salida
+        }
+
+
+        pub fn get_cant_candidatos_vot(&self)->i32{
+            let x = self.candidatos.len();
+            x as i32
+        }
+
+        pub fn get_cant_votantes_vot(&self)->i32{
+            let x = self.votantes.len();
+            x as i32
+        }
+        
+        pub fn inicio(&self, momento:Timestamp)->bool{  // trabajar con fechas
+            momento > self.fecha_inicio
+        }
+
+        pub fn finalizo(&self, momento:Timestamp)->bool{ // trabajar con fechas
+            momento > self.fecha_fin
+        }
+
+        //Con todos los compromisos cargados, la clave publica se interpola con los primeros umbral custodios, y cada uno de los demas compromisos tiene que caer en el mismo polinomio
+        fn calcular_clave_publica(&mut self){
+            self.clave_publica = None;
+            if let Some(custodia) = &self.custodia{
+                let compromisos: Vec<RistrettoPoint> = self.compromisos_custodios.iter().flatten().filter_map(|c| punto_de(*c)).collect();
+                if compromisos.len() < custodia.custodios.len(){
+                    return
+                }
+                let umbral = custodia.umbral as usize;
+                let indices: Vec<u64> = (1..=umbral as u64).collect();
+                let interpolar = |z:u64| compromisos[..umbral].iter().enumerate().fold(RistrettoPoint::identity(), |acc, (i, a)| acc + coeficiente_lagrange(&indices, i, z) * a);
+                let clave = interpolar(0);
+                if (umbral..compromisos.len()).all(|j| interpolar(j as u64 + 1) == compromisos[j]) && clave != RistrettoPoint::identity(){
+                    self.clave_publica = Some(clave.compress().to_bytes());
+                }else{
+                    ink::env::debug_println!("Los compromisos de los custodios de la votacion {} no son consistentes",self.id);
+                }
+            }
+        }
+
+        //Reconstruye la clave con las primeras umbral partes publicadas y suma cada boleta descifrada, buscando la opcion entre 1*G, 2*G, ... Las que no son una opcion valida se cuentan como nulas
+        fn descifrar_boletas(&mut self)->Descifrado{
+            let (custodios, umbral) = match &self.custodia {
+                Some(c) => (c.custodios.clone(), c.umbral as usize),
+                None => return Descifrado{ custodios:Vec::new(), nulas:0 },
+            };
+            let partes: Vec<(u64,Scalar)> = self.partes_clave.iter().enumerate().filter_map(|(i, p)| p.and_then(escalar_de).map(|p| (i as u64 + 1, p))).collect();
+            if partes.len() < umbral{
+                panic!("FALTAN PARTES DE LA CLAVE, HAY {} DE {}",partes.len(),umbral);
+            }
+            let indices: Vec<u64> = partes[..umbral].iter().map(|p| p.0).collect();
+            let clave: Scalar = partes[..umbral].iter().enumerate().map(|(i, p)| p.1 * coeficiente_lagrange(&indices, i, 0)).sum();
+            if self.clave_publica != Some(RistrettoPoint::mul_base(&clave).compress().to_bytes()){
+                panic!("LA CLAVE RECONSTRUIDA NO CORRESPONDE CON LA CLAVE PUBLICA");
+            }
+            let cant_opciones = match &self.tipo {
+                TipoVotacion::Referendum(consulta) => consulta.opciones.len(),
+                _ => self.candidatos.len(),
+            };
+            let mut nulas: u32 = 0;
+            for (c1, c2) in self.boletas_cifradas.clone() {
+                let opcion = punto_de(c1).zip(punto_de(c2)).and_then(|(c1, c2)| {
+                    let m = c2 - clave * c1;
+                    (1..=cant_opciones).scan(RistrettoPoint::identity(), |g, opcion| {
+                        *g += RISTRETTO_BASEPOINT_POINT;
+                        Some((opcion, *g))
+                    }).find(|(_, g)| *g == m)
+                });
+                match opcion {
+                    Some((opcion, _)) => self.sumar_boleta(Boleta::Simple(opcion as i32), None, 1),
+                    None => nulas = nulas.wrapping_add(1),
+                }
+            }
+            Descifrado{ custodios:partes.iter().map(|p| custodios[(p.0 as usize).saturating_sub(1)]).collect(), nulas }
+        }
+
+        fn posicion_custodio(&self, acc_id:AccountId)->usize{
+            self.custodia.as_ref().and_then(|c| c.custodios.iter().position(|x| *x == acc_id)).unwrap_or_else(|| panic!("NO SOS CUSTODIO DE ESTA VOTACION"))
+        }
+
+        //En una votacion secreta el resultado se calcula recien cuando termina el plazo para revelar
+        pub fn fin_revelacion(&self)->Timestamp{
+            self.fecha_fin.saturating_add((self.dias_revelacion.unwrap_or(0) as Timestamp).saturating_mul(DIA))
+        }
+
+
+
+        pub fn es_votante(&self, acc_id:AccountId)->bool{
+            self.votantes.contains(&acc_id)
+        }
+
+        //En una votacion abierta no hay lista de votantes: puede votar cualquiera que no sea candidato (los requisitos se chequean aparte)
+        pub fn esta_habilitado_para_votar(&self, acc_id:AccountId)->bool{
+            match self.modo_inscripcion {
+                ModoInscripcion::Abierta if !self.padron_congelado() => !self.es_candidato(acc_id) || self.requisitos.candidatos_pueden_votar,
+                _ => self.es_votante(acc_id),
+            }
+        }
+
+        pub fn es_candidato(&self, acc_id:AccountId)->bool{
+            self.candidatos.contains(&acc_id)
+        }
+
+        //No se puede sumar un rol que la cuenta ya tiene, ni el otro rol salvo que la votacion permita a los candidatos votar
+        pub fn puede_sumar_rol(&self, acc_id:AccountId, rol:&Rol)->bool{
+            let (mismo, otro) = match rol {
+                Rol::Votante => (self.es_votante(acc_id), self.es_candidato(acc_id)),
+                Rol::Candidato => (self.es_candidato(acc_id), self.es_votante(acc_id)),
+            };
+            !mismo && (!otro || self.requisitos.candidatos_pueden_votar)
+        }
+
+        pub fn sumar_candidato(&mut self,accid:AccountId){
+            if let TipoVotacion::Referendum(_) = self.tipo{
+                panic!("UN REFERENDUM NO TIENE CANDIDATOS");
+            }
+            if self.tipo.usa_ranking() && self.candidatos.len() > u8::MAX as usize{  // las boletas guardan la posicion en un u8
+                panic!("LA VOTACION YA TIENE LA CANTIDAD MAXIMA DE CANDIDATOS");
+            }
+            self.candidatos.push(accid);
+            self.votos.insert(accid, 0);
+        }
+
+        pub fn sumar_votante(&mut self,accid:AccountId){
+            if self.padron_congelado(){
+                panic!("LA LISTA DE VOTANTES ESTA CONGELADA");
+            }
+            self.votantes.push(accid);
+        }
+
+        //Las votaciones de un grupo de boletas que no son la principal no tienen votantes propios
+        pub fn chequear_padron_propio(&self){
+            if let Some(principal) = self.padron_de{
+                panic!("LA VOTACION {} USA EL PADRON DE LA VOTACION {}",self.id,principal);
+            }
+        }
+
+        pub fn padron_congelado(&self)->bool{
+            self.hash_padron_congelado.is_some()
+        }
+
+        pub fn quitar_candidato(&mut self,accid:AccountId){
+            self.candidatos.retain(|c| *c != accid);
+            self.votos.remove(&accid);
+        }
+
+        pub fn quitar_votante(&mut self,accid:AccountId){
+            self.votantes.retain(|v| *v != accid);
+        }
+
+        pub fn ya_voto(&self, acc_id:AccountId)->bool{
+            self.votaron.contains(&acc_id)
+        }
+
+        //Reemplaza la cuenta en todos los roles de la votacion. Si la cuenta vieja ya voto, la nueva queda como que ya voto
+        pub fn migrar_cuenta(&mut self, vieja:AccountId, nueva:AccountId){
+            self.candidatos.iter_mut().chain(self.votantes.iter_mut()).chain(self.votaron.iter_mut()).for_each(|c| {
+                if *c == vieja {
+                    *c = nueva;
+                }
+            });
+            if let Some(cant) = self.votos.remove(&vieja){
+                self.votos.insert(nueva, cant);
+            }
+            if let Some(saldo) = self.saldos.remove(&vieja){
+                self.saldos.insert(nueva, saldo);
+            }else if self.token.is_some(){
+                let titular = self.titulares_saldo.remove(&vieja).unwrap_or(vieja);  // el saldo de la foto sigue siendo el de la cuenta original
+                self.titulares_saldo.insert(nueva, titular);
+            }
+            if let Some(compromiso) = self.compromisos.remove(&vieja){
+                self.compromisos.insert(nueva, compromiso);
+            }
+            if let Some(clave) = self.claves_anonimas.remove(&vieja){
+                self.claves_anonimas.insert(nueva, clave);
+            }
+            self.revelados.iter_mut().filter(|c| **c == vieja).for_each(|c| *c = nueva);
+        }
+
+        pub fn sumar_voto(&mut self,pos:usize,peso:u128){
+            self.votos.entry(self.candidatos[pos]).and_modify(|c|* c = c.saturating_add(peso));
+        }
+
+        pub fn sumar_puntos(&mut self,pos:usize,puntos:u32){
+            self.votos.entry(self.candidatos[pos]).and_modify(|c|* c = c.saturating_add(puntos as u128));
+        }
+
+        //Sin token cada voto vale 1. Con token, el saldo de la foto (0 si todavia no voto)
+        pub fn peso_de(&self, acc_id:AccountId)->u128{
+            match self.token {
+                Some(_) => self.saldos.get(&acc_id).copied().unwrap_or(0),
+                None => 1,
+            }
+        }
+
+        fn sumar_a_claustro(&mut self, claustro:Option<usize>, pos:usize, puntos:u32){
+            if let Some(k) = claustro{
+                let c = self.votos_claustro[k].1.entry(self.candidatos[pos]).or_insert(0);
+                *c = c.saturating_add(puntos);
+            }
+        }
+
+        //Posicion del claustro de la categoria del votante. None si la votacion no pondera por claustros
+        pub fn claustro_de(&self, categoria:&Option<String>)->Option<usize>{
+            self.claustros.as_ref().map(|claustros| {
+                claustros.iter().position(|c| Some(&c.categoria) == categoria.as_ref()).unwrap_or_else(|| panic!("TU CATEGORIA NO CORRESPONDE A NINGUN CLAUSTRO DE ESTA VOTACION"))
+            })
+        }
+
+        pub fn ver_votos(&self,pos:i32)->u128{
+            if let Some(x)=self.votos.get(&self.candidatos[pos as usize]){
+                return *x
+            }
+            0
+        }
+
+        fn posicion_candidato(&self, acc_id:AccountId)->Option<usize>{
+            self.candidatos.iter().position(|c| *c == acc_id)
+        }
+
+        //Guarda la boleta si corresponde con el tipo de votacion y es valida
+        //Si la votacion pondera por claustros, los votos tambien se suman al claustro del votante. Si pondera por token, cada voto suma el saldo del votante
+        pub fn registrar_boleta(&mut self, boleta:Boleta, votante:&Usuario){
+            let claustro = self.claustro_de(&votante.categoria);
+            let peso = self.peso_de(votante.acc_id);
+            if peso == 0{
+                panic!("NO TENIAS SALDO DEL TOKEN CUANDO INICIO LA VOTACION");
+            }
+            self.sumar_boleta(boleta, claustro, peso);
+        }
+
+        //En una votacion anonima no se sabe quien vota, asi que la boleta se suma sin claustro y con peso 1
+        fn sumar_boleta(&mut self, boleta:Boleta, claustro:Option<usize>, peso:u128){
+            if let Some(k) = claustro{
+                self.votos_claustro[k].0 = self.votos_claustro[k].0.wrapping_add(1);
+            }
+            match (&self.tipo, boleta) {
+                (TipoVotacion::Mayoria | TipoVotacion::Proporcional{ .. }, Boleta::Simple(opcion)) => {
+                    match opcion.checked_sub(1) {
+                        Some(op) if op >= 0 && (op as usize) < self.candidatos.len() => {
+                            self.sumar_voto(op as usize, peso);
+                            self.sumar_a_claustro(claustro, op as usize, 1);
+                        },
+                        _ => panic!("OPCION INVALIDA"),
+                    }
+                },
+                (TipoVotacion::SegundaVueltaInstantanea | TipoVotacion::Schulze, Boleta::Ranking(ranking)) => {
+                    if ranking.is_empty(){
+                        panic!("EL RANKING NO PUEDE ESTAR VACIO");
+                    }
+                    let mut posiciones: Vec<u8> = Vec::new();
+                    ranking.iter().for_each(|c| {
+                        match self.posicion_candidato(*c) {
+                            Some(pos) if !posiciones.contains(&(pos as u8)) => posiciones.push(pos as u8),
+                            Some(_) => panic!("EL RANKING TIENE CANDIDATOS REPETIDOS"),
+                            None => panic!("EL RANKING TIENE CUENTAS QUE NO SON CANDIDATOS"),
+                        }
+                    });
+                    self.sumar_voto(posiciones[0] as usize, peso);  // en votos quedan las primeras preferencias
+                    self.boletas_ranking.push(posiciones);
+                },
+                (TipoVotacion::Aprobacion{ max_aprobados }, Boleta::Aprobacion(mut aprobados)) => {
+                    aprobados.sort_unstable();
+                    aprobados.dedup();
+                    if aprobados.is_empty() || aprobados.len() > *max_aprobados as usize{
+                        panic!("TENES QUE APROBAR ENTRE 1 Y {} CANDIDATOS",max_aprobados);
+                    }
+                    let posiciones: Vec<usize> = aprobados.iter().map(|c| self.posicion_candidato(*c).unwrap_or_else(|| panic!("LA BOLETA TIENE CUENTAS QUE NO SON CANDIDATOS"))).collect();
+                    posiciones.into_iter().for_each(|pos| {
+                        self.sumar_voto(pos, peso);
+                        self.sumar_a_claustro(claustro, pos, 1);
+                    });
+                },
+                (TipoVotacion::Puntaje{ creditos, cuadratico }, Boleta::Puntajes(puntajes)) => {
+                    let mut gastados: u64 = 0;
+                    let mut posiciones: Vec<(usize,u32)> = Vec::new();
+                    puntajes.iter().for_each(|(c, puntos)| {
+                        match self.posicion_candidato(*c) {
+                            Some(pos) if !posiciones.iter().any(|p| p.0 == pos) => posiciones.push((pos, *puntos)),
+                            Some(_) => panic!("LA BOLETA TIENE CANDIDATOS REPETIDOS"),
+                            None => panic!("LA BOLETA TIENE CUENTAS QUE NO SON CANDIDATOS"),
+                        }
+                        let costo = if *cuadratico { (*puntos as u64).saturating_mul(*puntos as u64) } else { *puntos as u64 };
+                        gastados = gastados.saturating_add(costo);
+                    });
+                    if gastados > *creditos as u64{
+                        panic!("LA BOLETA USA {} CREDITOS Y SOLO TENES {}",gastados,creditos);
+                    }
+                    posiciones.into_iter().for_each(|(pos, puntos)| {
+                        self.sumar_puntos(pos, puntos);
+                        self.sumar_a_claustro(claustro, pos, puntos);
+                    });
+                },
+                (TipoVotacion::Referendum(_), Boleta::Simple(opcion)) => {
+                    match opcion.checked_sub(1) {
+                        Some(op) if op >= 0 && (op as usize) < self.votos_opciones.len() => {
+                            self.votos_opciones[op as usize] = self.votos_opciones[op as usize].wrapping_add(1);
+                        },
+                        _ => panic!("OPCION INVALIDA"),
+                    }
+                },
+                _ => panic!("LA BOLETA NO CORRESPONDE CON EL TIPO DE VOTACION"),
+            }
+        }
+
+        //Votos de cada candidato de mayor a menor, a igualdad de votos queda primero el que se postulo antes
+        fn conteo_ordenado(&self)->Vec<(AccountId,u128)>{
+            let mut conteo: Vec<(AccountId,u128)> = self.candidatos.iter().map(|c| (*c, self.votos.get(c).copied().unwrap_or(0))).collect();
+            conteo.sort_by_key(|c| core::cmp::Reverse(c.1));
+            conteo
+        }
+
+        //El primero gana solo si tiene votos y mas que el segundo
+        fn ganador_unico<T:PartialOrd + Default>(conteo:&[(AccountId,T)])->Option<AccountId>{
+            match conteo {
+                [primero, segundo, ..] if primero.1 > segundo.1 => Some(primero.0),
+                [unico] if unico.1 > T::default() => Some(unico.0),
+                _ => None,
+            }
+        }
+
+        pub fn calcular_resultado(&self)->Resultado{
+            let conteo = self.conteo_ordenado();
+            let mut resultado = match self.tipo {
+                TipoVotacion::Mayoria => Resultado{ ganador:Self::ganador_unico(&conteo), conteo, detalle:DetalleResultado::Mayoria, ponderacion:None, sin_revelar:Vec::new(), descifrado:None },
+                TipoVotacion::SegundaVueltaInstantanea => {
+                    let (ganador, rondas) = self.calcular_segunda_vuelta();
+                    Resultado{ ganador, conteo, detalle:DetalleResultado::SegundaVueltaInstantanea(rondas), ponderacion:None, sin_revelar:Vec::new(), descifrado:None }
+                },
+                TipoVotacion::Aprobacion{ .. } => Resultado{ ganador:Self::ganador_unico(&conteo), conteo, detalle:DetalleResultado::Aprobacion, ponderacion:None, sin_revelar:Vec::new(), descifrado:None },
+                TipoVotacion::Puntaje{ .. } => Resultado{ ganador:Self::ganador_unico(&conteo), conteo, detalle:DetalleResultado::Puntaje, ponderacion:None, sin_revelar:Vec::new(), descifrado:None },
+                TipoVotacion::Referendum(ref consulta) => {
+                    let conteo_opciones = consulta.opciones.iter().cloned().zip(self.votos_opciones.iter().copied()).collect();
+                    let detalle = DetalleResultado::Referendum{ pregunta:consulta.pregunta.clone(), conteo:conteo_opciones, aprobado:consulta.esta_aprobada(&self.votos_opciones) };
+                    Resultado{ ganador:None, conteo, detalle, ponderacion:None, sin_revelar:Vec::new(), descifrado:None }
+                },
+                TipoVotacion::Schulze => {
+                    let (ganador, detalle) = self.calcular_schulze();
+                    Resultado{ ganador, conteo, detalle, ponderacion:None, sin_revelar:Vec::new(), descifrado:None }
+                },
+                TipoVotacion::Proporcional{ bancas, ref metodo, umbral_porcentaje } => {
+                    let detalle = Self::calcular_reparto(&conteo, bancas, metodo, umbral_porcentaje);
+                    Resultado{ ganador:Self::ganador_unico(&conteo), conteo, detalle, ponderacion:None, sin_revelar:Vec::new(), descifrado:None }  // el ganador es la lista mas votada
+                },
+            };
+            if let Some(ponderacion) = self.calcular_ponderacion(){
+                resultado.ganador = Self::ganador_unico(&ponderacion.conteo);
+                resultado.ponderacion = Some(ponderacion);
+            }
+            if self.dias_revelacion.is_some(){
+                resultado.sin_revelar = self.compromisos.keys().filter(|acc| !self.revelados.contains(acc)).copied().collect();
+            }
+            resultado
+        }
+
+        //Cada claustro reparte su peso entre los candidatos en proporcion a los votos que recibieron dentro del claustro, asi el resultado no depende de cuantos votantes tiene cada uno
+        fn calcular_ponderacion(&self)->Option<ConteoPonderado>{
+            let claustros = self.claustros.as_ref()?;
+            let mut ponderado: Vec<(AccountId,u64)> = self.candidatos.iter().map(|c| (*c, 0)).collect();
+            let conteos = claustros.iter().zip(self.votos_claustro.iter()).map(|(claustro, (votaron, votos))| {
+                let mut conteo: Vec<(AccountId,u32)> = self.candidatos.iter().map(|c| (*c, votos.get(c).copied().unwrap_or(0))).collect();
+                if *votaron > 0{
+                    ponderado.iter_mut().zip(conteo.iter()).for_each(|(p, c)| {
+                        let parte = (claustro.peso as u64).saturating_mul(c.1 as u64).saturating_mul(ESCALA_PONDERACION) / (*votaron as u64);
+                        p.1 = p.1.saturating_add(parte);
+                    });
+                }
+                conteo.sort_by_key(|c| core::cmp::Reverse(c.1));
+                ConteoClaustro{ categoria:claustro.categoria.clone(), peso:claustro.peso, votaron:*votaron, conteo }
+            }).collect();
+            ponderado.sort_by_key(|c| core::cmp::Reverse(c.1));
+            Some(ConteoPonderado{ claustros:conteos, conteo:ponderado })
+        }
+
+        //Un candidato del ranking se prefiere a todos los que estan despues y a todos los que no figuran; los que no figuran quedan empatados entre si
+        //Los caminos mas fuertes se calculan con Floyd-Warshall (la fuerza de un camino es la de su par mas debil). Gana el unico que le gana o empata a todos por camino
+        fn calcular_schulze(&self)->(Option<AccountId>, DetalleResultado){
+            let n = self.candidatos.len();
+            let mut preferencias: Vec<Vec<u32>> = (0..n).map(|_| (0..n).map(|_| 0).collect()).collect();
+            self.boletas_ranking.iter().for_each(|b| {
+                b.iter().enumerate().for_each(|(lugar, i)| {
+                    let i = *i as usize;
+                    (0..n).filter(|j| *j != i && !b[..lugar].contains(&(*j as u8))).for_each(|j| {
+                        preferencias[i][j] = preferencias[i][j].wrapping_add(1);
+                    });
+                });
+            });
+
+            let mut caminos: Vec<Vec<u32>> = (0..n).map(|i| (0..n).map(|j| {
+                if i != j && preferencias[i][j] > preferencias[j][i] { preferencias[i][j] } else { 0 }
+            }).collect()).collect();
+            for k in 0..n {
+                for i in (0..n).filter(|i| *i != k) {
+                    for j in (0..n).filter(|j| *j != k && *j != i) {
+                        let por_k = caminos[i][k].min(caminos[k][j]);
+                        if por_k > caminos[i][j] {
+                            caminos[i][j] = por_k;
+                        }
+                    }
+                }
+            }
+
+            let ganadores: Vec<usize> = (0..n).filter(|i| (0..n).all(|j| caminos[*i][j] >= caminos[j][*i])).collect();
+            let ganador = match ganadores.as_slice() {
+                [g] if !self.boletas_ranking.is_empty() => Some(self.candidatos[*g]),
+                _ => None,
+            };
+            let ganador_condorcet = (0..n).find(|i| (0..n).filter(|j| j != i).all(|j| preferencias[*i][j] > preferencias[j][*i])).map(|i| self.candidatos[i]);
+            (ganador, DetalleResultado::Schulze{ candidatos:self.candidatos.clone(), preferencias, caminos, ganador_condorcet })
+        }
+
+        //Reparte las bancas de a una a la lista con mayor cociente votos/divisor entre las que superan el umbral sobre el total de votos
+        //Los cocientes se comparan multiplicando en cruz para no perder precision. A igualdad de cociente gana la lista con mas votos, y despues la que se postulo antes
+        fn calcular_reparto(conteo:&[(AccountId,u128)], bancas:u32, metodo:&MetodoReparto, umbral_porcentaje:u32)->DetalleResultado{
+            let total: u128 = conteo.iter().fold(0u128, |t, c| t.saturating_add(c.1));
+            let habilitadas: Vec<(AccountId,u128)> = conteo.iter().filter(|c| c.1 > 0 && c.1.saturating_mul(100) >= total.saturating_mul(umbral_porcentaje as u128)).copied().collect();
+            let mut asignadas: Vec<u32> = habilitadas.iter().map(|_| 0).collect();
+            if !habilitadas.is_empty(){
+                for _ in 0..bancas {
+                    let mut mejor = 0;
+                    for i in 1..habilitadas.len() {
+                        let actual = habilitadas[i].1.saturating_mul(metodo.divisor(asignadas[mejor]) as u128);
+                        let del_mejor = habilitadas[mejor].1.saturating_mul(metodo.divisor(asignadas[i]) as u128);
+                        if actual > del_mejor {  // como conteo viene ordenado por votos, en un empate se queda la que ya estaba
+                            mejor = i;
+                        }
+                    }
+                    asignadas[mejor] = asignadas[mejor].saturating_add(1);
+                }
+            }
+            let cocientes = habilitadas.iter().map(|(lista, votos)| {
+                let fila = (0..bancas).map(|b| votos.saturating_mul(1000) / metodo.divisor(b) as u128).collect();
+                (*lista, fila)
+            }).collect();
+            let mut reparto: Vec<(AccountId,u32)> = habilitadas.iter().zip(asignadas).filter(|(_, b)| *b > 0).map(|(l, b)| (l.0, b)).collect();
+            reparto.sort_by_key(|r| core::cmp::Reverse(r.1));
+            DetalleResultado::Proporcional{ bancas:reparto, cocientes }
+        }
+
+        //En cada ronda cada boleta cuenta para su candidato preferido que siga en carrera. Si nadie tiene mayoria absoluta de las boletas no agotadas
+        //se elimina al que tiene menos votos (a igualdad, el que se postulo ultimo) y se pasa a la ronda siguiente. Si todos los que quedan empatan no hay ganador
+        fn calcular_segunda_vuelta(&self)->(Option<AccountId>, Vec<RondaEliminacion>){
+            let n = self.candidatos.len();
+            let mut en_carrera: Vec<bool> = (0..n).map(|_| true).collect();
+            let mut rondas: Vec<RondaEliminacion> = Vec::new();
+            let preferido = |boleta:&Vec<u8>, en_carrera:&Vec<bool>| boleta.iter().find(|c| en_carrera[**c as usize]).map(|c| *c as usize);
+            loop {
+                let mut votos: Vec<u32> = (0..n).map(|_| 0).collect();
+                let mut agotadas: u32 = 0;
+                self.boletas_ranking.iter().for_each(|b| {
+                    match preferido(b, &en_carrera) {
+                        Some(c) => votos[c] = votos[c].wrapping_add(1),
+                        None => agotadas = agotadas.wrapping_add(1),
+                    }
+                });
+                let validas: u32 = votos.iter().sum();
+                let conteo: Vec<(AccountId,u32)> = (0..n).filter(|i| en_carrera[*i]).map(|i| (self.candidatos[i], votos[i])).collect();
+
+                if let Some(g) = (0..n).find(|i| en_carrera[*i] && votos[*i].saturating_mul(2) > validas){
+                    rondas.push(RondaEliminacion{ conteo, agotadas, eliminado:None, transferencias:Vec::new() });
+                    return (Some(self.candidatos[g]), rondas)
+                }
+                let minimo = conteo.iter().map(|c| c.1).min().unwrap_or(0);
+                if conteo.iter().all(|c| c.1 == minimo){
+                    rondas.push(RondaEliminacion{ conteo, agotadas, eliminado:None, transferencias:Vec::new() });
+                    return (None, rondas)
+                }
+                let eliminado = (0..n).rev().find(|i| en_carrera[*i] && votos[*i] == minimo).unwrap_or(0);
+                let antes = en_carrera.clone();
+                en_carrera[eliminado] = false;
+
+                let mut transferidos: Vec<u32> = (0..n).map(|_| 0).collect();
+                self.boletas_ranking.iter().for_each(|b| {
+                    if preferido(b, &antes) == Some(eliminado){
+                        if let Some(c) = preferido(b, &en_carrera){
+                            transferidos[c] = transferidos[c].wrapping_add(1);
+                        }
+                    }
+                });
+                let transferencias = (0..n).filter(|i| transferidos[*i] > 0).map(|i| (self.candidatos[i], transferidos[i])).collect();
+                rondas.push(RondaEliminacion{ conteo, agotadas, eliminado:Some(self.candidatos[eliminado]), transferencias });
+            }
+        }
+
+
+    }
+
+
+    
+    #[ink(storage)]
+    pub struct Sistema {
+        nombre_administrador:String,
+        espera_usuarios: Vec<Usuario>,
+        usuarios_reg: Vec<Usuario>, // hashmap con account id
+        espera_candidatos:Vec<(AccountId,i32)>,
+        espera_votantes:Vec<(AccountId,i32)>,
+        votaciones:Vec<Votacion>,  // hashmap con id de votacion
+        registro_suspensiones:Vec<RegistroSuspension>,
+        espera_cambios_perfil:Vec<SolicitudCambioPerfil>,
+        rechazos:Vec<Rechazo>,
+        espera_migraciones:Vec<(AccountId,AccountId)>,  // (cuenta actual, cuenta nueva)
+        oraculo:Option<AccountId>,  // si esta configurado, los registros que confirma se aprueban solos
+        invitaciones:BTreeMap<[u8; 32],Option<Timestamp>>,  // hash de (codigo, cuenta) -> vencimiento
+        grupos_boletas:Vec<GrupoBoletas>,
+        admin:AccountId,
+    }
+    
+
+    impl Sistema {
+        
+        //Constructor que recibe unicamente el nombre del administrador
+        #[ink(constructor)]
+        pub fn new(nombre_administrador: String) -> Self {
+            Self { nombre_administrador,espera_usuarios:Vec::new(),espera_candidatos:Vec::new(),espera_votantes:Vec::new(), usuarios_reg:Vec::new(),votaciones: Vec::new(), registro_suspensiones:Vec::new(), espera_cambios_perfil:Vec::new(), rechazos:Vec::new(), espera_migraciones:Vec::new(), oraculo:None, invitaciones:BTreeMap::new(), grupos_boletas:Vec::new(), admin: Self::env().caller() }
+        }
+
+
+        //Crea un usuario verificando que no sea el administrador, que sea mayor de edad segun su fecha de nacimiento y que no este repetido y lo agrega a la lista de espera de aprobacion del administrador
+        //Si presenta un codigo de invitacion valido queda aprobado en el momento y el codigo ya no se puede volver a usar
+        #[ink(message)]
+        pub fn registrar_usuario(&mut self, nom:String,apellido:String,fecha_nacimiento:Fecha, dni:i128, codigo_invitacion:Option<String>) {
+            self.registrar_usuario_impl(nom, apellido, fecha_nacimiento, dni, codigo_invitacion);
+        }
+
+        fn registrar_usuario_impl(&mut self, nom:String,apellido:String,fecha_nacimiento:Fecha, dni:i128, codigo_invitacion:Option<String>) {
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if caller != self.admin {  //el administrador no se puede registrar como un usuario 
+                if !fecha_nacimiento.es_fecha_de_nacimiento_valida(momento){
+                    panic!("FECHA DE NACIMIENTO INVALIDA");
+                }
+                let mut aux: Usuario = Usuario::new(nom, apellido, dni, fecha_nacimiento, false, None, caller);
+                if aux.edad(momento) < EDAD_MINIMA_REGISTRO {
+                    panic!("DEBES TENER AL MENOS {} AÑOS PARA REGISTRARTE", EDAD_MINIMA_REGISTRO);
+                }
+                if self.registro_suspensiones.iter().any(|r| r.dni == dni && r.accion == AccionSuspension::Baja){  // un dni dado de baja no puede volver a registrarse
+                    panic!("ESE DNI FUE DADO DE BAJA DEL SISTEMA");
+                }
+                if  !self.usuarios_reg.iter().any(|u| u.dni == dni || u.acc_id == caller) {  // no puede haber dos usuarios con el mismo dni 
+                    self.chequear_reintento(caller, dni, TipoSolicitud::Registro, momento);
+                    if !self.espera_usuarios.contains(&aux){
+                        if let Some(codigo) = codigo_invitacion{
+                            self.usar_invitacion(&codigo, caller, dni, momento);
+                            ink::env::debug_println!("Registro del usuario {} {} aprobado por invitacion",aux.nombre,aux.apellido);
+                            aux.verificado = true;
+                            aux.fecha_alta = momento;
+                            self.usuarios_reg.push(aux);
+                        }else if self.oraculo_confirma(dni, caller){  // si el oraculo lo confirma no hace falta que lo valide el administrador
+                            ink::env::debug_println!("Registro del usuario {} {} confirmado por el oraculo",aux.nombre,aux.apellido);
+                            aux.verificado = true;
+                            aux.fecha_alta = momento;
+                            self.usuarios_reg.push(aux);
+                        }else{
+                            self.espera_usuarios.push(aux);
+                        }
+                    }else{
+                        panic!("ESTE USUARIO YA ESTA EN ESPERA DE VALIDACION");
+                    }
+                    
+                }else{
+                    panic!("CUENTA O DNI YA REGISTRADO");
+                }
+            }
+        }
+
+
+        //Si no hay oraculo configurado, o la llamada falla, o no confirma el dni, el registro queda para revision manual
+        fn oraculo_confirma(&self, dni:i128, cuenta:AccountId)->bool{
+            if let Some(addr) = self.oraculo{
+                let dni_hash = self.env().hash_encoded::<Blake2x256, _>(&dni);
+                let oraculo: ink::contract_ref!(OraculoIdentidad) = addr.into();
+                return matches!(oraculo.call().verificar_dni(dni_hash, cuenta).try_invoke(), Ok(Ok(true)))
+            }
+            false
+        }
+
+        //Quema el codigo si es valido para la cuenta y el dni que se registran, si no lo es se rechaza el registro entero
+        fn usar_invitacion(&mut self, codigo:&str, cuenta:AccountId, dni:i128, momento:Timestamp){
+            let hash = self.hash_codigo(codigo, cuenta, dni);
+            match self.invitaciones.remove(&hash){
+                Some(Some(vencimiento)) if momento > vencimiento => panic!("EL CODIGO DE INVITACION ESTA VENCIDO"),
+                Some(_) => {},
+                None => panic!("CODIGO DE INVITACION INVALIDO"),
+            }
+        }
+
+        //El codigo va atado a la cuenta invitada, asi alguien que lo copie de una transaccion pendiente no lo puede usar desde otra cuenta,
+        //y al dni invitado, asi la invitacion no sirve para registrar sin control un dni que es de otra persona
+        fn hash_codigo(&self, codigo:&str, cuenta:AccountId, dni:i128)->[u8; 32]{
+            self.env().hash_encoded::<Blake2x256, _>(&(codigo, cuenta, dni))
+        }
+
+        //Unicamente el administrador puede publicar codigos de invitacion, como hashes Blake2x256 de la codificacion SCALE de (codigo, cuenta invitada, dni invitado). Si se da una fecha de vencimiento, los codigos valen hasta el final de ese dia
+        #[ink(message)]
+        pub fn publicar_invitaciones(&mut self, hashes:Vec<[u8; 32]>, vencimiento:Option<Fecha>) {
+            self.publicar_invitaciones_impl(hashes, vencimiento);
+        }
+
+        fn publicar_invitaciones_impl(&mut self, hashes:Vec<[u8; 32]>, vencimiento:Option<Fecha>){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE PUBLICAR INVITACIONES");
+            }
+            let venc = vencimiento.map(|f| {
+                if !f.es_fecha_valida(){
+                    panic!("FECHA INVALIDA");
+                }
+                f.to_timestamp()
+            });
+            hashes.into_iter().for_each(|h| { self.invitaciones.insert(h, venc); });
+        }
+
+        //Unicamente el administrador puede revocar codigos de invitacion que todavia no se usaron
+        #[ink(message)]
+        pub fn revocar_invitaciones(&mut self, hashes:Vec<[u8; 32]>) {
+            self.revocar_invitaciones_impl(hashes);
+        }
+
+        fn revocar_invitaciones_impl(&mut self, hashes:Vec<[u8; 32]>){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE REVOCAR INVITACIONES");
+            }
+            hashes.iter().for_each(|h| { self.invitaciones.remove(h); });
+        }
+
+        //Unicamente el administrador puede configurar (o quitar, pasando None) el contrato oraculo que verifica los dni
+        #[ink(message)]
+        pub fn configurar_oraculo(&mut self, oraculo:Option<AccountId>) {
+            self.configurar_oraculo_impl(oraculo);
+        }
+
+        fn configurar_oraculo_impl(&mut self, oraculo:Option<AccountId>){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE CONFIGURAR EL ORACULO");
+            }
+            self.oraculo = oraculo;
+        }
+
+
+        //Unicamente el administrador puede validar o rechazar un usuario que solicito registrarse. Si lo rechaza el motivo queda guardado para que el usuario lo pueda consultar
+        #[ink(message)] 
+        pub fn validar_usuario(&mut self, aceptar: bool, motivo_rechazo: Option<String>) {
+            self.validar_usuario_impl(aceptar, motivo_rechazo);
+        }
+    
+        fn validar_usuario_impl(&mut self, aceptar:bool, motivo_rechazo:Option<String>){
+            let mut aux: Option<String> = None;
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if caller == self.admin {  // solo el administrador puede validar candidatos 
+                if !self.espera_usuarios.is_empty() {  // checkea si hay candidatos a validar, y si hay se empieza a trabajar el primero
+                    let mut us = self.espera_usuarios[0].clone();
+                    us.fecha_alta = momento;
+                    let mut s1 = us.nombre.clone();
+                    let s2 = String::from(" ");
+                    let s3 = us.apellido.clone();
+                    s1.push_str(&s2);
+                    s1.push_str(&s3);
+                    aux = Some(s1);   
+                    if aceptar{  // el admin decide si aceptar o rechazar el candidato
+                        self.usuarios_reg.push(us)
+                    }else{
+                        self.rechazos.push(Rechazo{ acc_id:us.acc_id, dni:us.dni, tipo:TipoSolicitud::Registro, motivo:motivo_rechazo.unwrap_or_default(), momento });
+                    }
+                    self.espera_usuarios.remove(0);  // se elimina de la cola de espera de aprobacion 
+                }
+            }
+            if let Some(a) =aux{
+                ink::env::debug_println!("Aceptar solicitud de registro del usuario  {:?}",a);
+            } else{
+                ink::env::debug_println!("No hay solicitudes de registro");
+            }
+        }
+
+        //Despues de un rechazo hay que esperar ESPERA_REINTENTO para volver a solicitar, y despues de MAX_RECHAZOS no se puede solicitar mas. Se cuenta por cuenta y por dni
+        fn chequear_reintento(&self, acc_id:AccountId, dni:i128, tipo:TipoSolicitud, momento:Timestamp){
+            let mut cant = 0;
+            let mut ultimo: Option<Timestamp> = None;
+            self.rechazos.iter().filter(|r| r.tipo == tipo && (r.acc_id == acc_id || r.dni == dni)).for_each(|r| {
+                cant += 1;
+                ultimo = Some(r.momento);
+            });
+            if cant >= MAX_RECHAZOS {
+                panic!("SUPERASTE EL LIMITE DE {} SOLICITUDES RECHAZADAS",MAX_RECHAZOS);
+            }
+            if let Some(u) = ultimo {
+                if momento < u.saturating_add(ESPERA_REINTENTO) {
+                    panic!("TU ULTIMA SOLICITUD FUE RECHAZADA, PODES VOLVER A INTENTAR DESDE timestamp: {}",u.saturating_add(ESPERA_REINTENTO));
+                }
+            }
+        }
+
+        //Devuelve las solicitudes rechazadas del usuario que llama, con el motivo que dio el administrador
+        #[ink(message)]
+        pub fn get_mis_rechazos(&self)->Vec<Rechazo>{
+            let caller = self.env().caller();
+            self.rechazos.iter().filter(|r| r.acc_id == caller).cloned().collect()
+        }
+
+
+        //Un usuario registrado puede pedir cambiar su nombre y/o apellido. El cambio queda en espera hasta que el administrador lo apruebe
+        #[ink(message)]
+        pub fn solicitar_cambio_perfil(&mut self, nombre:Option<String>, apellido:Option<String>) {
+            self.solicitar_cambio_perfil_impl(nombre, apellido);
+        }
+
+        fn solicitar_cambio_perfil_impl(&mut self, nombre:Option<String>, apellido:Option<String>){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if nombre.is_none() && apellido.is_none(){
+                panic!("NO HAY CAMBIOS PARA SOLICITAR");
+            }
+            if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                if us.suspendido{
+                    panic!("TU USUARIO ESTA SUSPENDIDO");
+                }
+                if self.espera_cambios_perfil.iter().any(|s| s.acc_id == caller){
+                    panic!("YA TENES UN CAMBIO DE PERFIL EN ESPERA DE VALIDACION");
+                }
+                self.chequear_reintento(caller, us.dni, TipoSolicitud::CambioPerfil, momento);
+                self.espera_cambios_perfil.push(SolicitudCambioPerfil{ acc_id:caller, nombre, apellido });
+            }else{
+                panic!("NO ESTAS REGISTRADO O VALIDADO EN EL SISTEMA");
+            }
+        }
+
+        //Unicamente el administrador puede aprobar o rechazar el primer cambio de perfil en espera
+        #[ink(message)]
+        pub fn validar_cambio_perfil(&mut self, aceptar: bool, motivo_rechazo: Option<String>) {
+            self.validar_cambio_perfil_impl(aceptar, motivo_rechazo);
+        }
+
+        fn validar_cambio_perfil_impl(&mut self, aceptar:bool, motivo_rechazo:Option<String>){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE VALIDAR CAMBIOS DE PERFIL");
+            }
+            if self.espera_cambios_perfil.is_empty(){
+                ink::env::debug_println!("No hay solicitudes de cambio de perfil");
+                return
+            }
+            let sol = self.espera_cambios_perfil.remove(0);
+            if let Some(u) = self.usuarios_reg.iter_mut().find(|u| u.acc_id == sol.acc_id){  // si el usuario fue dado de baja mientras tanto la solicitud se descarta
+                if aceptar{
+                    if let Some(n) = sol.nombre{
+                        u.nombre = n;
+                    }
+                    if let Some(a) = sol.apellido{
+                        u.apellido = a;
+                    }
+                    ink::env::debug_println!("Cambio de perfil aceptado: {} {}",u.nombre,u.apellido);
+                }else{
+                    self.rechazos.push(Rechazo{ acc_id:u.acc_id, dni:u.dni, tipo:TipoSolicitud::CambioPerfil, motivo:motivo_rechazo.unwrap_or_default(), momento });
+                }
+            }
+        }
+
+
+        //Unicamente el administrador puede asignarle una categoria a un usuario registrado, que despues se usa en los requisitos de cada votacion
+        #[ink(message)]
+        pub fn asignar_categoria(&mut self, acc_id:AccountId, categoria:String) {
+            self.asignar_categoria_impl(acc_id, categoria);
+        }
+
+        fn asignar_categoria_impl(&mut self, acc_id:AccountId, categoria:String){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE ASIGNAR CATEGORIAS");
+            }
+            self.congelar_padrones_iniciados();  // el cambio no afecta a las votaciones que ya iniciaron
+            if let Some(u) = self.usuarios_reg.iter_mut().find(|u| u.acc_id == acc_id){
+                u.categoria = Some(categoria);
+            }else{
+                panic!("NO EXISTE UN USUARIO REGISTRADO CON ESA CUENTA");
+            }
+        }
+
+
+        //Unicamente el administrador puede suspender a un usuario registrado (por ejemplo si se registro de forma fraudulenta). Se lo quita de las candidaturas de las votaciones que todavia no iniciaron
+        #[ink(message)]
+        pub fn suspender_usuario(&mut self, acc_id:AccountId, motivo:String) {
+            self.suspender_usuario_impl(acc_id, motivo);
+        }
+
+        fn suspender_usuario_impl(&mut self, acc_id:AccountId, motivo:String){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE SUSPENDER USUARIOS");
+            }
+            let dni = if let Some(u) = self.usuarios_reg.iter_mut().find(|u| u.acc_id == acc_id){
+                if u.suspendido {
+                    panic!("EL USUARIO YA ESTA SUSPENDIDO");
+                }
+                u.suspendido = true;
+                u.dni
+            }else{
+                panic!("NO EXISTE UN USUARIO REGISTRADO CON ESA CUENTA");
+            };
+            self.quitar_de_votaciones_no_iniciadas(acc_id, false);
+            self.registrar_suspension(acc_id, dni, AccionSuspension::Suspension, motivo);
+        }
+
+        //Unicamente el administrador puede rehabilitar a un usuario suspendido. Las candidaturas que perdio no se recuperan, tiene que volver a postularse
+        #[ink(message)]
+        pub fn rehabilitar_usuario(&mut self, acc_id:AccountId, motivo:String) {
+            self.rehabilitar_usuario_impl(acc_id, motivo);
+        }
+
+        fn rehabilitar_usuario_impl(&mut self, acc_id:AccountId, motivo:String){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE REHABILITAR USUARIOS");
+            }
+            let dni = if let Some(u) = self.usuarios_reg.iter_mut().find(|u| u.acc_id == acc_id){
+                if !u.suspendido {
+                    panic!("EL USUARIO NO ESTA SUSPENDIDO");
+                }
+                u.suspendido = false;
+                u.dni
+            }else{
+                panic!("NO EXISTE UN USUARIO REGISTRADO CON ESA CUENTA");
+            };
+            self.registrar_suspension(acc_id, dni, AccionSuspension::Rehabilitacion, motivo);
+        }
+
+        //Unicamente el administrador puede dar de baja a un usuario. Se lo elimina del sistema y de las votaciones que todavia no iniciaron (en las que ya iniciaron se conservan sus votos). Su dni no se puede volver a registrar
+        #[ink(message)]
+        pub fn eliminar_usuario(&mut self, acc_id:AccountId, motivo:String) {
+            self.eliminar_usuario_impl(acc_id, motivo);
+        }
+
+        fn eliminar_usuario_impl(&mut self, acc_id:AccountId, motivo:String){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE ELIMINAR USUARIOS");
+            }
+            self.congelar_padrones_iniciados();  // en las votaciones que ya iniciaron sigue en el padron y se conservan sus votos
+            let dni = if let Some(pos) = self.usuarios_reg.iter().position(|u| u.acc_id == acc_id){
+                self.usuarios_reg.remove(pos).dni
+            }else{
+                panic!("NO EXISTE UN USUARIO REGISTRADO CON ESA CUENTA");
+            };
+            self.quitar_de_votaciones_no_iniciadas(acc_id, true);
+            self.registrar_suspension(acc_id, dni, AccionSuspension::Baja, motivo);
+        }
+
+        fn quitar_de_votaciones_no_iniciadas(&mut self, acc_id:AccountId, tambien_votante:bool){
+            let momento = self.env().block_timestamp();
+            self.votaciones.iter_mut().filter(|v| !v.inicio(momento)).for_each(|v| {
+                v.quitar_candidato(acc_id);
+                v.claves_anonimas.remove(&acc_id);  // si lo reactivan puede volver a registrar una clave antes del inicio
+                if tambien_votante {
+                    v.quitar_votante(acc_id);
+                }
+            });
+            self.espera_candidatos.retain(|(acc,_)| *acc != acc_id);
+            self.espera_votantes.retain(|(acc,_)| *acc != acc_id);
+        }
+
+        //Estado de suspension del dni en un momento dado, segun el historial (el dni no cambia si el usuario migra de cuenta)
+        fn suspendido_en(&self, dni:i128, momento:Timestamp)->bool{
+            self.registro_suspensiones.iter().rev()
+                .find(|r| r.dni == dni && r.momento <= momento && r.accion != AccionSuspension::Baja)
+                .is_some_and(|r| r.accion == AccionSuspension::Suspension)
+        }
+
+        //Padron de una votacion abierta tal como estaba a su fecha de inicio: usuarios dados de alta hasta ese momento, no suspendidos en ese momento y que cumplian los requisitos con la edad a esa fecha
+        //Las categorias y las bajas no tienen historial, por eso antes de cambiar una categoria o dar de baja a alguien se congelan los padrones de las votaciones que ya iniciaron
+        fn padron_abierto(&self, v:&Votacion)->Vec<AccountId>{
+            self.usuarios_reg.iter()
+                .filter(|u| u.fecha_alta <= v.fecha_inicio && !self.suspendido_en(u.dni, v.fecha_inicio) && v.esta_habilitado_para_votar(u.acc_id) && v.requisitos.cumple(u, &Rol::Votante, v.fecha_inicio))
+                .map(|u| u.acc_id).collect()
+        }
+
+        fn congelar_padrones_iniciados(&mut self){
+            let momento = self.env().block_timestamp();
+            let iniciadas: Vec<i32> = self.votaciones.iter().filter(|v| v.inicio(momento) && !v.padron_congelado()).map(|v| v.id).collect();
+            iniciadas.into_iter().for_each(|id| self.congelar_padron_si_inicio(id, momento));
+        }
+
+        fn registrar_suspension(&mut self, acc_id:AccountId, dni:i128, accion:AccionSuspension, motivo:String){
+            let momento = self.env().block_timestamp();
+            ink::env::debug_println!("{:?} del usuario con dni {}: {}",accion,dni,motivo);
+            self.registro_suspensiones.push(RegistroSuspension{ acc_id, dni, accion, motivo, momento });
+        }
+
+        //Devuelve el historial de suspensiones, rehabilitaciones y bajas. Si se pasa una cuenta se filtra por ella
+        #[ink(message)]
+        pub fn get_registro_suspensiones(&self, acc_id:Option<AccountId>)->Vec<RegistroSuspension>{
+            self.registro_suspensiones.iter().filter(|r| acc_id.is_none_or(|a| r.acc_id == a)).cloned().collect()
+        }
+
+
+        //Un usuario registrado pide mudar su identidad a una cuenta nueva. La migracion se hace cuando la cuenta nueva la confirma con confirmar_migracion_cuenta
+        #[ink(message)]
+        pub fn solicitar_migracion_cuenta(&mut self, nueva_cuenta:AccountId) {
+            self.solicitar_migracion_cuenta_impl(nueva_cuenta);
+        }
+
+        fn solicitar_migracion_cuenta_impl(&mut self, nueva_cuenta:AccountId){
+            let caller = self.env().caller();
+            if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                if us.suspendido{
+                    panic!("TU USUARIO ESTA SUSPENDIDO");
+                }
+                self.chequear_cuenta_libre(nueva_cuenta);
+                self.espera_migraciones.retain(|(vieja,_)| *vieja != caller);  // una nueva solicitud reemplaza a la anterior
+                self.espera_migraciones.push((caller, nueva_cuenta));
+            }else{
+                panic!("NO ESTAS REGISTRADO O VALIDADO EN EL SISTEMA");
+            }
+        }
+
+        //La cuenta nueva confirma que le pertenece al usuario, y se le pasan su identidad, sus solicitudes pendientes y sus roles en las votaciones
+        #[ink(message)]
+        pub fn confirmar_migracion_cuenta(&mut self, cuenta_anterior:AccountId) {
+            self.confirmar_migracion_cuenta_impl(cuenta_anterior);
+        }
+
+        fn confirmar_migracion_cuenta_impl(&mut self, cuenta_anterior:AccountId){
+            let caller = self.env().caller();
+            if let Some(pos) = self.espera_migraciones.iter().position(|m| *m == (cuenta_anterior, caller)){
+                self.espera_migraciones.remove(pos);
+                self.chequear_cuenta_libre(caller);
+                self.migrar_cuenta(cuenta_anterior, caller);
+            }else{
+                panic!("NO HAY UNA MIGRACION PENDIENTE DE ESA CUENTA A LA TUYA");
+            }
+        }
+
+        //Unicamente el administrador puede migrar la identidad de un usuario que perdio su cuenta, volviendo a verificar su dni
+        #[ink(message)]
+        pub fn migrar_cuenta_usuario(&mut self, dni:i128, cuenta_anterior:AccountId, nueva_cuenta:AccountId) {
+            self.migrar_cuenta_usuario_impl(dni, cuenta_anterior, nueva_cuenta);
+        }
+
+        fn migrar_cuenta_usuario_impl(&mut self, dni:i128, cuenta_anterior:AccountId, nueva_cuenta:AccountId){
+            let caller = self.env().caller();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE MIGRAR CUENTAS DE OTROS USUARIOS");
+            }
+            if !self.usuarios_reg.iter().any(|u| u.acc_id == cuenta_anterior && u.dni == dni){
+                panic!("EL DNI NO CORRESPONDE AL USUARIO DE ESA CUENTA");
+            }
+            self.chequear_cuenta_libre(nueva_cuenta);
+            self.migrar_cuenta(cuenta_anterior, nueva_cuenta);
+        }
+
+        fn chequear_cuenta_libre(&self, cuenta:AccountId){
+            if cuenta == self.admin || self.usuarios_reg.iter().any(|u| u.acc_id == cuenta) || self.espera_usuarios.iter().any(|u| u.acc_id == cuenta){
+                panic!("LA CUENTA NUEVA YA ESTA EN USO");
+            }
+            if self.votaciones.iter().any(|v| v.es_votante(cuenta) || v.es_candidato(cuenta) || v.ya_voto(cuenta) || v.claves_anonimas.contains_key(&cuenta)){
+                panic!("LA CUENTA NUEVA YA PARTICIPA DE UNA VOTACION");
+            }
+        }
+
+        fn migrar_cuenta(&mut self, vieja:AccountId, nueva:AccountId){
+            if let Some(u) = self.usuarios_reg.iter_mut().find(|u| u.acc_id == vieja){
+                u.acc_id = nueva;
+            }
+            self.espera_candidatos.iter_mut().chain(self.espera_votantes.iter_mut()).for_each(|(acc,_)| {
+                if *acc == vieja {
+                    *acc = nueva;
+                }
+            });
+            self.espera_cambios_perfil.iter_mut().filter(|s| s.acc_id == vieja).for_each(|s| s.acc_id = nueva);
+            self.espera_migraciones.retain(|(v,_)| *v != vieja);
+            self.votaciones.iter_mut().for_each(|v| v.migrar_cuenta(vieja, nueva));
+            ink::env::debug_println!("Cuenta {:?} migrada a {:?}",vieja,nueva);
+        }
+
+
+        //Unicamente el administrador puede crear una votacion. No puede haber dos votaciones con el mismo id, las fechas de inicio y fin deben ser validas y los requisitos no pueden pedir una edad menor a la de registro
+        #[ink(message)]
+        pub fn crear_votacion(&mut self, id:i32, puesto:String, inicio:Fecha, fin:Fecha, config:ConfiguracionVotacion) {
+            self.crear_votacion_impl(id, puesto, inicio, fin, config);
+        }
+
+        fn crear_votacion_impl(&mut self, id:i32, puesto:String,fecha_inicio:Fecha,fecha_fin:Fecha,config:ConfiguracionVotacion){ 
+            let caller = self.env().caller();
+            if !fecha_inicio.es_fecha_valida() | !fecha_fin.es_fecha_valida(){
                 panic!("FECHA INVALIDA");
             }
+            if !config.requisitos.son_validos(){
+                panic!("REQUISITOS INVALIDOS, LA EDAD MINIMA ES {}",EDAD_MINIMA_REGISTRO);
+            }
+            if !config.tipo.es_valido(){
+                panic!("TIPO DE VOTACION INVALIDO");
+            }
+            if !config.claustros_validos(){
+                panic!("PONDERACION POR CLAUSTROS INVALIDA");
+            }
+            if !config.token_valido(){
+                panic!("PONDERACION POR TOKEN INVALIDA");
+            }
+            if !config.nft_valido(){
+                panic!("UNA VOTACION CON NFT TIENE QUE SER ABIERTA");
+            }
+            if !config.secreto_valido(){
+                panic!("VOTO SECRETO INVALIDO");
+            }
+            if !config.anonima_valida(){
+                panic!("UNA VOTACION ANONIMA NO SE PUEDE PONDERAR NI SER SECRETA POR COMPROMISO");
+            }
+            if !config.custodia_valida(){
+                panic!("CUSTODIA DE CLAVE INVALIDA");
+            }
             if caller == self.admin {  //solo el administrador puede crear votaciones
                 if !self.votaciones.iter().any(|v|v.id==id){  //no se tiene que poder crear dos votaciones con el mismo id
-                    let v = Votacion::new(id, puesto, fecha_inicio.to_timestamp(),fecha_fin.to_timestamp());
+                    if let Some(token) = config.token{  // un PSP22 comun no tiene saldos historicos y no se podria consultar la foto al votar
+                        if self.saldo_token(token, caller, self.env().block_timestamp().saturating_sub(1)).is_none(){
+                            panic!("EL TOKEN NO PERMITE CONSULTAR SALDOS HISTORICOS");
+                        }
+                    }
+                    let v = Votacion::new(id, puesto, config, fecha_inicio.to_timestamp(),fecha_fin.to_timestamp());
                     self.votaciones.push(v);       
                     ink::env::debug_println!("fecha inicio: {:?} timestamp: {}",fecha_inicio,fecha_inicio.to_timestamp().wrapping_sub(86_400_000)); //asi comienza ese dia a las 00:00
                     ink::env::debug_println!("fecha fin: {:?} timestamp: {}",fecha_fin,fecha_fin.to_timestamp().wrapping_sub(1));  //asi termina ese dia a las 23:59:59.999
                 }
 
             }
-                
+                
+        }
+
+        //Los usuarios que se registraron y ya fueron validados por el administrador pueden postularse como candidato o como votante a una votacion (antes de que esta haya comenzado), y esperar a que el administrador los acepte o rechace
+        #[ink(message)]
+        pub fn postularse_a_votacion(&mut self,rol:Rol, id_de_votacion:i32) {
+            self.postularse_a_votacion_impl(rol,id_de_votacion);
+        }
+
+       
+        fn postularse_a_votacion_impl(&mut self,rol:Rol, id_de_votacion:i32){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+                if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){   // como el administrador no puede registrarse, si se intenta postular aca va a dar falso
+                    if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){  //si existe la votacion a la que se quiere postular 
+                        if v.inicio(momento){
+                            panic!("LA VOTACION YA INICIO timestamp vot:{}",v.fecha_inicio);
+                        }
+                        if us.suspendido{
+                            panic!("TU USUARIO ESTA SUSPENDIDO");
+                        }
+                        if !v.requisitos.cumple(us, &rol, v.fecha_inicio){ // la edad se toma al dia de inicio de la votacion
+                            panic!("NO CUMPLIS LOS REQUISITOS PARA POSTULARTE A ESTA VOTACION");
+                        }
+                        if let (Rol::Candidato, TipoVotacion::Referendum(_)) = (&rol, &v.tipo){
+                            panic!("UN REFERENDUM NO TIENE CANDIDATOS");
+                        }
+                        if let Rol::Votante = rol {
+                            v.chequear_padron_propio();
+                            match v.modo_inscripcion {
+                                ModoInscripcion::Abierta => panic!("LA VOTACION ES ABIERTA, NO HACE FALTA POSTULARSE PARA VOTAR"),
+                                ModoInscripcion::PadronCerrado => panic!("LOS VOTANTES DE ESTA VOTACION LOS CARGA EL ADMINISTRADOR"),
+                                ModoInscripcion::Postulacion => {},
+                            }
+                        }
+                        if v.puede_sumar_rol(caller, &rol){ // si ya no esta postulado con ese rol (o con el otro, si la votacion no permite los dos)
+                            let (espera_mismo, espera_otro) = match rol {
+                                Rol::Candidato => (&self.espera_candidatos, &self.espera_votantes),
+                                Rol::Votante => (&self.espera_votantes, &self.espera_candidatos),
+                            };
+                            let ya_postulado = espera_mismo.contains(&(caller,id_de_votacion)) || (espera_otro.contains(&(caller,id_de_votacion)) && !v.requisitos.candidatos_pueden_votar);
+                            if !ya_postulado{
+                                match rol{ 
+                                Rol::Candidato=>{ self.espera_candidatos.push((caller,id_de_votacion)); }, 
+                                Rol::Votante=> {  self.espera_votantes.push((caller,id_de_votacion)); }
+                                }
+                            }else{
+                                panic!("YA TE POSTULASTE A ESTA VOTACION");
+                            }
+                            
+                        }
+                    }else{
+                        panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+                    } 
+                } else {
+                    panic!("NO ESTAS REGISTRADO O VALIDADO EN EL SISTEMA");
+                }
+            ink::env::debug_println!("timestamp actual: {}",momento);
+
+        }
+
+        //Unicamente el administrador puede cargar la lista de votantes de una votacion con padron cerrado (antes de que inicie). Solo se cargan usuarios registrados, no suspendidos y que cumplan los requisitos
+        //La lista se suma a los votantes que ya tenia la votacion (por cargas anteriores, agregar_votantes o inscribirse_con_padron), no los reemplaza. Devuelve las cuentas que no se pudieron cargar y por que (las que ya estaban, como YaInscripto)
+        #[ink(message)]
+        pub fn cargar_padron_cerrado(&mut self, id_de_votacion:i32, votantes:Vec<AccountId>) -> Vec<(AccountId,ErrorInscripcion)> {
+            self.cargar_padron_cerrado_impl(id_de_votacion, votantes)
+        }
+
+        fn cargar_padron_cerrado_impl(&mut self, id_de_votacion:i32, votantes:Vec<AccountId>) -> Vec<(AccountId,ErrorInscripcion)> {
+            let pos = self.votacion_para_carga(id_de_votacion);
+            self.votaciones[pos].chequear_padron_propio();
+            if self.votaciones[pos].modo_inscripcion != ModoInscripcion::PadronCerrado{
+                panic!("LA VOTACION NO ES DE PADRON CERRADO");
+            }
+            let mut errores = Vec::new();
+            for acc in votantes {
+                if let Err(e) = self.inscribir(pos, acc, Rol::Votante){
+                    errores.push((acc, e));
+                }
+            }
+            errores
+        }
+
+        //Unicamente el administrador puede agregar de una sola vez una lista de usuarios (por cuenta o por dni) como votantes de una votacion que no inicio y no es abierta
+        //Devuelve los usuarios que no se pudieron agregar y por que, el resto queda inscripto
+        #[ink(message)]
+        pub fn agregar_votantes(&mut self, id_de_votacion:i32, usuarios:Vec<IdentificadorUsuario>) -> Vec<(IdentificadorUsuario,ErrorInscripcion)> {
+            self.agregar_votantes_impl(id_de_votacion, usuarios)
+        }
+
+        fn agregar_votantes_impl(&mut self, id_de_votacion:i32, usuarios:Vec<IdentificadorUsuario>) -> Vec<(IdentificadorUsuario,ErrorInscripcion)> {
+            let pos = self.votacion_para_carga(id_de_votacion);
+            self.votaciones[pos].chequear_padron_propio();
+            if self.votaciones[pos].modo_inscripcion == ModoInscripcion::Abierta{
+                panic!("LA VOTACION ES ABIERTA, NO TIENE LISTA DE VOTANTES");
+            }
+            let mut errores = Vec::new();
+            for id in usuarios {
+                let cuenta = match &id {
+                    IdentificadorUsuario::Cuenta(acc) => Some(*acc),
+                    IdentificadorUsuario::Dni(dni) => self.usuarios_reg.iter().find(|u| u.dni == *dni).map(|u| u.acc_id),
+                };
+                let resultado = match cuenta {
+                    Some(acc) => self.inscribir(pos, acc, Rol::Votante),
+                    None => Err(ErrorInscripcion::NoRegistrado),
+                };
+                if let Err(e) = resultado{
+                    errores.push((id, e));
+                }
+            }
+            errores
+        }
+
+        //Unicamente el administrador puede copiar la lista de votantes (y si se pide tambien los candidatos) de una votacion a otra que no inicio
+        //Cada cuenta se vuelve a chequear con los requisitos de la votacion destino. Devuelve las que no se pudieron copiar y por que
+        //Si el origen es una votacion abierta que todavia no congelo su padron, se copian los usuarios que estarian en su padron a su fecha de inicio
+        #[ink(message)]
+        pub fn clonar_padron(&mut self, id_origen:i32, id_destino:i32, incluir_candidatos:bool) -> Vec<(AccountId,ErrorInscripcion)> {
+            self.clonar_padron_impl(id_origen, id_destino, incluir_candidatos)
+        }
+
+        fn clonar_padron_impl(&mut self, id_origen:i32, id_destino:i32, incluir_candidatos:bool) -> Vec<(AccountId,ErrorInscripcion)> {
+            let pos = self.votacion_para_carga(id_destino);
+            let (votantes, candidatos) = if let Some(origen) = self.votaciones.iter().position(|v| v.id == id_origen){
+                let padron = &self.votaciones[self.posicion_padron(origen)];
+                let votantes = if padron.modo_inscripcion == ModoInscripcion::Abierta && !padron.padron_congelado() { self.padron_abierto(padron) } else { padron.votantes.clone() };
+                (votantes, self.votaciones[origen].candidatos.clone())
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_origen);
+            };
+            let mut errores = Vec::new();
+            if self.votaciones[pos].modo_inscripcion != ModoInscripcion::Abierta{  // en una votacion abierta no hace falta copiar votantes
+                self.votaciones[pos].chequear_padron_propio();
+                for acc in votantes {
+                    if let Err(e) = self.inscribir(pos, acc, Rol::Votante){
+                        errores.push((acc, e));
+                    }
+                }
+            }
+            if incluir_candidatos && !matches!(self.votaciones[pos].tipo, TipoVotacion::Referendum(_)){  // un referendum no tiene candidatos
+                for acc in candidatos {
+                    if let Err(e) = self.inscribir(pos, acc, Rol::Candidato){
+                        errores.push((acc, e));
+                    }
+                }
+            }
+            errores
+        }
+
+        //Chequea que lo llame el administrador y que la votacion exista y no haya iniciado. Devuelve su posicion
+        fn votacion_para_carga(&self, id_de_votacion:i32)->usize{
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE CARGAR VOTANTES");
+            }
+            if let Some(pos) = self.votaciones.iter().position(|vot| vot.id == id_de_votacion){
+                if self.votaciones[pos].inicio(momento){
+                    panic!("LA VOTACION YA INICIO");
+                }
+                pos
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+            }
+        }
+
+        //Inscribe directamente una cuenta en la votacion, sin pasar por la espera de validacion
+        fn inscribir(&mut self, pos:usize, acc:AccountId, rol:Rol)->Result<(),ErrorInscripcion>{
+            let v = &self.votaciones[pos];
+            match self.usuarios_reg.iter().find(|u| u.acc_id == acc) {
+                None => return Err(ErrorInscripcion::NoRegistrado),
+                Some(u) if u.suspendido => return Err(ErrorInscripcion::Suspendido),
+                Some(u) if !v.requisitos.cumple(u, &rol, v.fecha_inicio) => return Err(ErrorInscripcion::NoCumpleRequisitos),
+                Some(_) if !v.puede_sumar_rol(acc, &rol) => return Err(ErrorInscripcion::YaInscripto),
+                Some(_) => {},
+            }
+            let id = v.id;
+            match rol {
+                Rol::Votante => {
+                    self.votaciones[pos].sumar_votante(acc);
+                    self.espera_votantes.retain(|e| *e != (acc, id));
+                },
+                Rol::Candidato => {
+                    self.votaciones[pos].sumar_candidato(acc);
+                    self.espera_candidatos.retain(|e| *e != (acc, id));
+                },
+            }
+            Ok(())
         }
 
-        //Los usuarios que se registraron y ya fueron validados por el administrador pueden postularse como candidato o como votante a una votacion (antes de que esta haya comenzado), y esperar a que el administrador los acepte o rechace
+        //Unicamente el administrador puede publicar el padron oficial de una votacion (antes de que inicie), como la raiz de merkle de los hashes Blake2x256 de los dni habilitados
         #[ink(message)]
-        pub fn postularse_a_votacion(&mut self,rol:Rol, id_de_votacion:i32) {
-            self.postularse_a_votacion_impl(rol,id_de_votacion);
+        pub fn publicar_padron(&mut self, id_de_votacion:i32, raiz:[u8; 32]) {
+            self.publicar_padron_impl(id_de_votacion, raiz);
         }
 
-       
-        fn postularse_a_votacion_impl(&mut self,rol:Rol, id_de_votacion:i32){
+        fn publicar_padron_impl(&mut self, id_de_votacion:i32, raiz:[u8; 32]){
             let caller = self.env().caller();
             let momento = self.env().block_timestamp();
-                if self.usuarios_reg.iter().any(|u| u.acc_id == caller){   // como el administrador no puede registrarse, si se intenta postular aca va a dar falso
-                    if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){  //si existe la votacion a la que se quiere postular 
-                        if v.inicio(momento){
-                            panic!("LA VOTACION YA INICIO timestamp vot:{}",v.fecha_inicio);
-                        }
-                        if !v.es_votante(caller) && !v.es_candidato(caller){ // si ya no esta postulado como votante o candidato
-                            if !self.espera_candidatos.contains(&(caller,id_de_votacion)) && !self.espera_votantes.contains(&(caller,id_de_votacion)){
-                                match rol{ 
-                                Rol::Candidato=>{ self.espera_candidatos.push((caller,id_de_votacion)); }, 
-                                Rol::Votante=> {  self.espera_votantes.push((caller,id_de_votacion)); }
-                                }
-                            }else{
-                                panic!("YA TE POSTULASTE A ESTA VOTACION");
-                            }
-                            
-                        }
-                    }else{
-                        panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
-                    } 
-                } else {
-                    panic!("NO ESTAS REGISTRADO O VALIDADO EN EL SISTEMA");
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE PUBLICAR EL PADRON");
+            }
+            if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){
+                if v.inicio(momento){
+                    panic!("LA VOTACION YA INICIO");
                 }
-            ink::env::debug_println!("timestamp actual: {}",momento);
+                v.padron = Some(raiz);
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+            }
+        }
+
+        //Un usuario registrado que figura en el padron de la votacion se inscribe como votante en el momento, presentando el camino de merkle de su dni, sin esperar al administrador
+        #[ink(message)]
+        pub fn inscribirse_con_padron(&mut self, id_de_votacion:i32, prueba:Vec<[u8; 32]>) {
+            self.inscribirse_con_padron_impl(id_de_votacion, prueba);
+        }
+
+        fn inscribirse_con_padron_impl(&mut self, id_de_votacion:i32, prueba:Vec<[u8; 32]>){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                let hoja = self.env().hash_encoded::<Blake2x256, _>(&us.dni);
+                if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){
+                    if v.inicio(momento){
+                        panic!("LA VOTACION YA INICIO timestamp vot:{}",v.fecha_inicio);
+                    }
+                    if us.suspendido{
+                        panic!("TU USUARIO ESTA SUSPENDIDO");
+                    }
+                    if !v.requisitos.cumple(us, &Rol::Votante, v.fecha_inicio){
+                        panic!("NO CUMPLIS LOS REQUISITOS PARA VOTAR EN ESTA VOTACION");
+                    }
+                    v.chequear_padron_propio();
+                    if v.modo_inscripcion == ModoInscripcion::Abierta{
+                        panic!("LA VOTACION ES ABIERTA, NO HACE FALTA INSCRIBIRSE PARA VOTAR");
+                    }
+                    if !v.puede_sumar_rol(caller, &Rol::Votante){
+                        panic!("YA ESTAS INSCRIPTO EN ESTA VOTACION");
+                    }
+                    if !v.esta_en_padron(hoja, &prueba){
+                        panic!("NO FIGURAS EN EL PADRON DE ESTA VOTACION");
+                    }
+                    v.sumar_votante(caller);
+                    self.espera_votantes.retain(|e| *e != (caller, id_de_votacion));  // ya no hace falta que el administrador valide la solicitud
+                }else{
+                    panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+                }
+            }else{
+                panic!("NO ESTAS REGISTRADO O VALIDADO EN EL SISTEMA");
+            }
+        }
+
+        //Cualquiera puede congelar la lista de votantes de una votacion que ya inicio (igual se congela sola con el primer voto)
+        #[ink(message)]
+        pub fn congelar_padron(&mut self, id_de_votacion:i32) {
+            let momento = self.env().block_timestamp();
+            if let Some(v) = self.votaciones.iter().find(|vot| vot.id == id_de_votacion){
+                if !v.inicio(momento){
+                    panic!("LA VOTACION TODAVIA NO INICIO");
+                }
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+            }
+            self.congelar_padron_si_inicio(id_de_votacion, momento);
+        }
+
+        //La primera vez que se usa una votacion despues de su inicio se fija su lista de votantes (en una votacion abierta, los usuarios que estaban habilitados a la fecha de inicio;
+        //en las demas, los inscriptos que cumplen los requisitos) y se guarda el hash de sus dni ordenados. Desde ahi la lista no cambia, asi la participacion y las auditorias se refieren siempre al mismo padron
+        fn congelar_padron_si_inicio(&mut self, id_de_votacion:i32, momento:Timestamp){
+            if let Some(pos) = self.votaciones.iter().position(|v| v.id == id_de_votacion){
+                let pos = self.posicion_padron(pos);  // en un grupo de boletas se congela el padron de la votacion principal
+                let v = &self.votaciones[pos];
+                if !v.inicio(momento) || v.padron_congelado(){
+                    return
+                }
+                let votantes: Vec<AccountId> = match v.modo_inscripcion {
+                    ModoInscripcion::Abierta => self.padron_abierto(v),
+                    _ => v.votantes.iter().filter(|acc| self.usuarios_reg.iter().any(|u| u.acc_id == **acc && v.requisitos.cumple(u, &Rol::Votante, v.fecha_inicio))).copied().collect(),
+                };
+                let mut dnis: Vec<i128> = votantes.iter().filter_map(|acc| self.usuarios_reg.iter().find(|u| u.acc_id == *acc).map(|u| u.dni)).collect();
+                dnis.sort_unstable();
+                let hash = self.env().hash_encoded::<Blake2x256, _>(&dnis);
+                let v = &mut self.votaciones[pos];
+                v.votantes = votantes;
+                v.hash_padron_congelado = Some(hash);
+                ink::env::debug_println!("Padron de la votacion {} congelado con {} votantes",v.id,v.votantes.len());
+            }
+        }
+
+        //En una votacion por token el saldo de cada votante se consulta recien cuando vota, pero siempre a la fecha de inicio: la foto queda fija en la apertura,
+        //asi transferir despues no cambia el peso ni da dos votos con los mismos tokens. Congelar el padron no hace una llamada por votante,
+        //y si el token falla solo se revierte el voto de ese votante, que puede volver a intentar
+        fn fijar_saldo(&mut self, pos:usize, cuenta:AccountId){
+            let v = &self.votaciones[pos];
+            if let (Some(token), true) = (v.token, v.padron_congelado()){
+                if v.saldos.contains_key(&cuenta){
+                    return
+                }
+                let titular = v.titulares_saldo.get(&cuenta).copied().unwrap_or(cuenta);
+                let saldo = self.saldo_token(token, titular, v.fecha_inicio).unwrap_or_else(|| panic!("NO SE PUDO CONSULTAR EL SALDO DEL TOKEN"));
+                self.votaciones[pos].saldos.insert(cuenta, saldo);
+            }
+        }
 
+        fn saldo_token(&self, token:AccountId, cuenta:AccountId, momento:Timestamp)->Option<u128>{
+            let psp22: ink::contract_ref!(PSP22Historico) = token.into();
+            match psp22.call().balance_of_at(cuenta, momento).try_invoke() {
+                Ok(Ok(saldo)) => Some(saldo),
+                _ => None,
+            }
         }
 
         //Unicamente el administrador puede validar o rechazar candidatos para las votaciones, siempre y cuando esta votacion no haya comenzado
@@ -368,10 +2211,13 @@ mod sistema {
                             aux = Some(s1); 
                             if let Some(vot) = self.votaciones.iter_mut().find(|v| v.id == vot_id){  // va a encontrar la votacion si o si ya que esto se checkea al postularse
                                 if vot.inicio(momento){ // Si la votacion ya inicio el administrador no deberia poder aceptarlo o rechazarlo, asique se "descarta" la solicituda de candidato
-                                    self.espera_candidatos.remove(0);
-                                }
-                                if aceptar{  // el admin decide si aceptar o rechazar el candidato
-                                    vot.sumar_candidato(acc_id);
+                                    ink::env::debug_println!("La votacion ya inicio, se descarta la solicitud");
+                                }else if aceptar{  // el admin decide si aceptar o rechazar el candidato
+                                    if vot.requisitos.cumple(u, &Rol::Candidato, vot.fecha_inicio) && vot.puede_sumar_rol(acc_id, &Rol::Candidato){  // los requisitos se vuelven a chequear por si cambio la categoria del usuario
+                                        vot.sumar_candidato(acc_id);
+                                    }else{
+                                        ink::env::debug_println!("El usuario no cumple los requisitos de la votacion, se descarta la solicitud");
+                                    }
                                 }
                             }
                             self.espera_candidatos.remove(0);  // se elimina de la cola de espera de aprobacion 
@@ -381,104 +2227,561 @@ mod sistema {
             }else{
                 panic!("SOLO EL ADMINISTRADOR PUEDE VALIDAR CANDIDATOS");
             }
-            if let Some(a) =aux{
-                ink::env::debug_println!("Aceptar solicitud de candidato del usuario {:?} para la votacion de id {}",a,vot_id);
+            if let Some(a) =aux{
+                ink::env::debug_println!("Aceptar solicitud de candidato del usuario {:?} para la votacion de id {}",a,vot_id);
+
+            } else{
+                ink::env::debug_println!("No hay solicitudes para candidatos");
+
+            }
+        }
+
+        //Unicamente el administrador puede validar o rechazar votantes para las votaciones, siempre y cuando esta votacion no haya comenzado
+        #[ink(message)]
+        pub fn validar_votante(&mut self, aceptar: bool) {
+            self.validar_votante_impl(aceptar);
+        }
+
+        fn validar_votante_impl(&mut self, aceptar:bool){
+            let mut aux: Option<String> = None;
+            let mut vot_id=0;
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if caller == self.admin {
+                if !self.espera_votantes.is_empty() {
+                    let acc_id = self.espera_votantes[0].0;
+                    vot_id = self.espera_votantes[0].1;
+                    
+                    self.usuarios_reg.iter_mut().for_each(|u| { //si ya estas en la espera de candidatos, si o si vas a estar en el vector de usuarios 
+                        if u.acc_id == acc_id {
+                            let mut s1 = u.nombre.clone();
+                            let s2 = String::from(" ");
+                            let s3 = u.apellido.clone();
+                            s1.push_str(&s2);
+                            s1.push_str(&s3);
+                            aux = Some(s1); 
+                            if let Some(vot) = self.votaciones.iter_mut().find(|v| v.id == vot_id){
+                                if vot.inicio(momento){
+                                    ink::env::debug_println!("La votacion ya inicio, se descarta la solicitud");
+                                }else if aceptar{
+                                    if vot.requisitos.cumple(u, &Rol::Votante, vot.fecha_inicio) && vot.puede_sumar_rol(acc_id, &Rol::Votante){
+                                        vot.sumar_votante(acc_id);
+                                    }else{
+                                        ink::env::debug_println!("El usuario no cumple los requisitos de la votacion, se descarta la solicitud");
+                                    }
+                                }
+                            }
+                            self.espera_votantes.remove(0);
+                        }
+                    })
+                }
+            }else{
+                panic!("SOLO EL ADMINISTRADOR PUEDE VALIDAR VOTANTES");
+            }
+            if let Some(a) =aux{
+                ink::env::debug_println!("Aceptar solicitud de votante del usuario {:?} para la votacion de id {}",a,vot_id);
+
+            } else{
+                ink::env::debug_println!("No hay solicitudes para votante");
+
+            }
+        }
+
+
+        //El votante puede votar validando su identidad (debe estar registrado y validado por el administrador). La boleta tiene que corresponder con el tipo de votacion
+        #[ink(message)]
+        pub fn votar(&mut self, id_de_votacion: i32, boleta:Boleta) {
+            self.votar_impl(id_de_votacion, boleta);
+        }
+        
+        fn votar_impl(&mut self,id_de_votacion:i32,boleta:Boleta){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            self.congelar_padron_si_inicio(id_de_votacion, momento);
+            if caller != self.admin{
+                if let Some(pos) = self.votaciones.iter().position(|vot| vot.id == id_de_votacion){
+                    if self.votaciones[pos].coleccion_nft.is_some(){
+                        panic!("EN ESTA VOTACION SE VOTA PRESENTANDO UN NFT");
+                    }
+                    if self.votaciones[pos].dias_revelacion.is_some(){
+                        panic!("EN ESTA VOTACION EL VOTO ES SECRETO, SE VOTA CON UN COMPROMISO");
+                    }
+                    if self.votaciones[pos].anonima{
+                        panic!("EN ESTA VOTACION SE VOTA DE FORMA ANONIMA CON UNA FIRMA DE ANILLO");
+                    }
+                    if self.votaciones[pos].custodia.is_some(){
+                        panic!("EN ESTA VOTACION SE VOTA CON UNA BOLETA CIFRADA");
+                    }
+                    if self.puede_votar(pos, caller, momento){ //Los candidatos solo pueden votar en su propia votacion si esta lo permite, ya que si no no van a estar registrados como votantes
+                        self.mostrar_opciones(&self.votaciones[pos]);
+                        self.fijar_saldo(pos, caller);
+                        let v = &mut self.votaciones[pos];
+                        if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                            v.registrar_boleta(boleta, us);
+                        }
+                        v.votaron.push(caller);
+                    }
+                }else{
+                    panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+                }
+            }
+
+        }
+
+        //En una votacion con NFT se vota presentando el id de un NFT de la coleccion que sea del que vota. Cada NFT se puede usar una sola vez, asi pasarlo a otra cuenta no da otro voto
+        #[ink(message)]
+        pub fn votar_con_nft(&mut self, id_de_votacion:i32, nft:Id, boleta:Boleta) {
+            self.votar_con_nft_impl(id_de_votacion, nft, boleta);
+        }
+
+        fn votar_con_nft_impl(&mut self, id_de_votacion:i32, nft:Id, boleta:Boleta){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            self.congelar_padron_si_inicio(id_de_votacion, momento);
+            let pos = self.votaciones.iter().position(|vot| vot.id == id_de_votacion).unwrap_or_else(|| panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion));
+            let coleccion = self.votaciones[pos].coleccion_nft.unwrap_or_else(|| panic!("ESTA VOTACION NO SE VOTA CON NFT"));
+            if !self.puede_votar(pos, caller, momento){
+                panic!("NO ESTAS HABILITADO PARA VOTAR EN ESTA VOTACION");
+            }
+            if self.votaciones[pos].nfts_usados.contains(&nft){
+                panic!("ESE NFT YA SE USO PARA VOTAR EN ESTA VOTACION");
+            }
+            if self.duenio_nft(coleccion, nft.clone()) != Some(caller){
+                panic!("ESE NFT NO ES TUYO");
+            }
+            self.mostrar_opciones(&self.votaciones[pos]);
+            self.fijar_saldo(pos, caller);
+            let v = &mut self.votaciones[pos];
+            if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                v.registrar_boleta(boleta, us);
+            }
+            v.votaron.push(caller);
+            v.nfts_usados.push(nft);
+        }
+
+        //En una votacion secreta se vota mandando el hash Blake2x256 de la codificacion SCALE de la tupla (cuenta del contrato, id de la votacion, cuenta del votante, boleta, sal), con la sal de 32 bytes al azar,
+        //asi nadie puede ver que se voto hasta que se revela, y nadie puede copiar el compromiso de otro para votar lo mismo. El hash se calcula fuera de la cadena
+        #[ink(message)]
+        pub fn votar_secreto(&mut self, id_de_votacion:i32, compromiso:[u8; 32]) {
+            self.votar_secreto_impl(id_de_votacion, compromiso);
+        }
+
+        fn votar_secreto_impl(&mut self, id_de_votacion:i32, compromiso:[u8; 32]){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            self.congelar_padron_si_inicio(id_de_votacion, momento);
+            let pos = self.votaciones.iter().position(|vot| vot.id == id_de_votacion).unwrap_or_else(|| panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion));
+            if self.votaciones[pos].dias_revelacion.is_none(){
+                panic!("ESTA VOTACION NO ES SECRETA");
+            }
+            if !self.puede_votar(pos, caller, momento){
+                panic!("NO ESTAS HABILITADO PARA VOTAR EN ESTA VOTACION");
+            }
+            let v = &mut self.votaciones[pos];
+            v.compromisos.insert(caller, (compromiso, caller));
+            v.votaron.push(caller);
+        }
+
+        //Despues de la fecha de fin y hasta que termina el plazo, cada votante revela su boleta y la sal. Solo se cuenta si coincide con el compromiso y la boleta es valida
+        #[ink(message)]
+        pub fn revelar_voto(&mut self, id_de_votacion:i32, boleta:Boleta, sal:[u8; 32]) {
+            self.revelar_voto_impl(id_de_votacion, boleta, sal);
+        }
+
+        fn revelar_voto_impl(&mut self, id_de_votacion:i32, boleta:Boleta, sal:[u8; 32]){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if let Some(pos) = self.votaciones.iter().position(|vot| vot.id == id_de_votacion){
+                let v = &self.votaciones[pos];
+                if !v.finalizo(momento){
+                    panic!("TODAVIA NO SE PUEDE REVELAR, LA VOTACION NO FINALIZO");
+                }
+                if momento > v.fin_revelacion(){
+                    panic!("TERMINO EL PLAZO PARA REVELAR");
+                }
+                match v.compromisos.get(&caller) {
+                    None => panic!("NO VOTASTE EN ESTA VOTACION"),
+                    Some(_) if v.revelados.contains(&caller) => panic!("YA REVELASTE TU VOTO"),
+                    Some((compromiso, cuenta)) if *compromiso != self.hash_voto_secreto(id_de_votacion, *cuenta, boleta.clone(), sal) => panic!("LA BOLETA Y LA SAL NO COINCIDEN CON TU COMPROMISO"),
+                    Some(_) => {},
+                }
+                self.fijar_saldo(pos, caller);
+                let v = &mut self.votaciones[pos];
+                if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                    v.registrar_boleta(boleta, us);
+                    v.revelados.push(caller);
+                }else{
+                    panic!("NO ESTAS REGISTRADO O VALIDADO EN EL SISTEMA");
+                }
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+            }
+        }
+
+        //En una votacion anonima, antes del inicio cada votante habilitado registra una sola vez una clave publica propia (un punto de Ristretto comprimido), distinta para cada votacion
+        #[ink(message)]
+        pub fn registrar_clave_anonima(&mut self, id_de_votacion:i32, clave:[u8; 32]) {
+            self.registrar_clave_anonima_impl(id_de_votacion, clave);
+        }
+
+        fn registrar_clave_anonima_impl(&mut self, id_de_votacion:i32, clave:[u8; 32]){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            let pos = self.votaciones.iter().position(|vot| vot.id == id_de_votacion).unwrap_or_else(|| panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion));
+            let v = &self.votaciones[pos];
+            if !v.anonima{
+                panic!("ESTA VOTACION NO ES ANONIMA");
+            }
+            if v.inicio(momento){
+                panic!("LA VOTACION YA INICIO, NO SE PUEDEN REGISTRAR CLAVES");
+            }
+            if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                if us.suspendido{
+                    panic!("TU USUARIO ESTA SUSPENDIDO");
+                }
+                if !v.requisitos.cumple(us, &Rol::Votante, v.fecha_inicio){
+                    panic!("NO CUMPLIS LOS REQUISITOS PARA VOTAR EN ESTA VOTACION");
+                }
+                let habilitado = match v.modo_inscripcion {
+                    ModoInscripcion::Abierta => v.esta_habilitado_para_votar(caller),
+                    _ => v.votantes.contains(&caller),
+                };
+                if !habilitado{
+                    panic!("NO ESTAS HABILITADO PARA VOTAR EN ESTA VOTACION");
+                }
+            }else{
+                panic!("NO ESTAS REGISTRADO O VALIDADO EN EL SISTEMA");
+            }
+            if v.claves_anonimas.contains_key(&caller){
+                panic!("YA REGISTRASTE TU CLAVE");
+            }
+            if punto_de(clave).is_none(){
+                panic!("LA CLAVE NO ES UN PUNTO VALIDO");
+            }
+            if v.claves_anonimas.values().any(|c| *c == clave){
+                panic!("ESA CLAVE YA ESTA REGISTRADA");
+            }
+            self.votaciones[pos].claves_anonimas.insert(caller, clave);
+        }
+
+        //Claves con las que todavia se puede votar: las de los votantes que siguen registrados, no estan suspendidos y estan en el padron (congelado, si la votacion ya inicio)
+        fn claves_habilitadas(&self, v:&Votacion)->BTreeSet<[u8; 32]>{
+            v.claves_anonimas.iter()
+                .filter(|(acc, _)| self.usuarios_reg.iter().any(|u| u.acc_id == **acc && !u.suspendido && v.esta_habilitado_para_votar(u.acc_id) && v.requisitos.cumple(u, &Rol::Votante, v.fecha_inicio)))
+                .map(|(_, clave)| *clave).collect()
+        }
+
+        //Se vota desde cualquier cuenta con una firma de anillo de la boleta hecha con la clave privada, escondida entre otras claves registradas. La cuenta que vota no se guarda,
+        //y la imagen de la clave es la misma en cualquier anillo, asi cada clave vota una sola vez. Lo que se firma es el hash Blake2x256 de la codificacion SCALE de
+        //(cuenta del contrato, id de la votacion, boleta). Para que el voto no se asocie al votante, conviene mandarlo desde una cuenta sin relacion con la que registro la clave
+        #[ink(message)]
+        pub fn votar_anonimo(&mut self, id_de_votacion:i32, boleta:Boleta, firma:FirmaAnillo) {
+            self.votar_anonimo_impl(id_de_votacion, boleta, firma);
+        }
+
+        fn votar_anonimo_impl(&mut self, id_de_votacion:i32, boleta:Boleta, firma:FirmaAnillo){
+            let momento = self.env().block_timestamp();
+            self.congelar_padron_si_inicio(id_de_votacion, momento);
+            let mensaje = self.mensaje_anonimo(id_de_votacion, &boleta);
+            if let Some(pos) = self.votaciones.iter().position(|vot| vot.id == id_de_votacion){
+                let habilitadas = self.claves_habilitadas(&self.votaciones[pos]);
+                let v = &mut self.votaciones[pos];
+                if !v.anonima{
+                    panic!("ESTA VOTACION NO ES ANONIMA");
+                }
+                if !v.inicio(momento){
+                    panic!("LA VOTACION TODAVIA NO INICIO");
+                }
+                if v.finalizo(momento){
+                    panic!("LA VOTACION FINALIZO");
+                }
+                if v.anuladores.contains(&firma.imagen){
+                    panic!("ESA CLAVE YA VOTO");
+                }
+                if firma.anillo.len() < ANILLO_MINIMO.min(habilitadas.len()){
+                    panic!("EL ANILLO TIENE MUY POCAS CLAVES");
+                }
+                if firma.anillo.iter().enumerate().any(|(i, c)| !habilitadas.contains(c) || firma.anillo[..i].contains(c)){
+                    panic!("EL ANILLO TIENE CLAVES NO HABILITADAS O REPETIDAS");
+                }
+                if !verificar_firma_anillo(mensaje, &firma){
+                    panic!("LA FIRMA NO ES VALIDA");
+                }
+                v.sumar_boleta(boleta, None, 1);
+                v.anuladores.push(firma.imagen);
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+            }
+        }
+
+        fn mensaje_anonimo(&self, id_de_votacion:i32, boleta:&Boleta)->[u8; 32]{
+            self.env().hash_encoded::<Blake2x256, _>(&(self.env().account_id(), id_de_votacion, boleta))
+        }
+
+        //Antes del inicio cada custodio carga parte*G (un punto de Ristretto comprimido, con G el punto base), donde parte es su parte de la clave. Se puede volver a cargar para corregirlo.
+        //Cuando estan todos y son consistentes queda la clave publica. Las partes se reparten por fuera de la cadena, con un repartidor o sumando los polinomios de cada custodio para que nadie conozca la clave entera
+        #[ink(message)]
+        pub fn comprometer_clave_custodio(&mut self, id_de_votacion:i32, compromiso:[u8; 32]) {
+            self.comprometer_clave_custodio_impl(id_de_votacion, compromiso);
+        }
+
+        fn comprometer_clave_custodio_impl(&mut self, id_de_votacion:i32, compromiso:[u8; 32]){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){
+                let i = v.posicion_custodio(caller);
+                if v.inicio(momento){
+                    panic!("LA VOTACION YA INICIO");
+                }
+                if punto_de(compromiso).is_none(){
+                    panic!("EL COMPROMISO NO ES UN PUNTO VALIDO");
+                }
+                v.compromisos_custodios[i] = Some(compromiso);
+                v.calcular_clave_publica();
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+            }
+        }
+
+        //Se vota mandando la boleta cifrada con ElGamal sobre Ristretto con la clave publica: (r*G, opcion*G + r*clave), como puntos comprimidos, con r un escalar al azar y la opcion empezando en 1.
+        //La boleta se cifra fuera de la cadena, asi ni la opcion ni r pasan por ningun nodo. Nadie puede contar los votos hasta que los custodios publiquen sus partes
+        #[ink(message)]
+        pub fn votar_cifrado(&mut self, id_de_votacion:i32, c1:[u8; 32], c2:[u8; 32]) {
+            self.votar_cifrado_impl(id_de_votacion, c1, c2);
+        }
+
+        fn votar_cifrado_impl(&mut self, id_de_votacion:i32, c1:[u8; 32], c2:[u8; 32]){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            self.congelar_padron_si_inicio(id_de_votacion, momento);
+            let pos = self.votaciones.iter().position(|vot| vot.id == id_de_votacion).unwrap_or_else(|| panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion));
+            if self.votaciones[pos].custodia.is_none(){
+                panic!("ESTA VOTACION NO ES CIFRADA");
+            }
+            if self.votaciones[pos].clave_publica.is_none(){
+                panic!("LA VOTACION NO TIENE CLAVE PUBLICA, FALTAN COMPROMISOS DE LOS CUSTODIOS");
+            }
+            if punto_de(c1).is_none() || punto_de(c2).is_none(){
+                panic!("LA BOLETA CIFRADA NO ES VALIDA");
+            }
+            if !self.puede_votar(pos, caller, momento){
+                panic!("NO ESTAS HABILITADO PARA VOTAR EN ESTA VOTACION");
+            }
+            let v = &mut self.votaciones[pos];
+            v.boletas_cifradas.push((c1, c2));
+            v.votaron.push(caller);
+        }
+
+        //Despues de la fecha de fin cada custodio publica su parte de la clave (un escalar en 32 bytes little endian), y solo se acepta si coincide con su compromiso. Con umbral partes se puede finalizar la votacion
+        #[ink(message)]
+        pub fn publicar_parte_clave(&mut self, id_de_votacion:i32, parte:[u8; 32]) {
+            self.publicar_parte_clave_impl(id_de_votacion, parte);
+        }
+
+        fn publicar_parte_clave_impl(&mut self, id_de_votacion:i32, parte:[u8; 32]){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){
+                let i = v.posicion_custodio(caller);
+                if !v.finalizo(momento){
+                    panic!("LA VOTACION TODAVIA NO FINALIZO");
+                }
+                if v.resultado.is_some(){
+                    panic!("LA VOTACION YA FUE FINALIZADA");
+                }
+                if escalar_de(parte).is_none_or(|p| v.compromisos_custodios[i] != Some(RistrettoPoint::mul_base(&p).compress().to_bytes())){
+                    panic!("LA PARTE NO COINCIDE CON TU COMPROMISO");
+                }
+                v.partes_clave[i] = Some(parte);
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
+            }
+        }
+
+        //El compromiso lo arma el votante fuera de la cadena con el mismo hash, asi la boleta y la sal no pasan por ningun nodo antes de revelar
+        fn hash_voto_secreto(&self, id_de_votacion:i32, cuenta:AccountId, boleta:Boleta, sal:[u8; 32]) -> [u8; 32] {
+            self.env().hash_encoded::<Blake2x256, _>(&(self.env().account_id(), id_de_votacion, cuenta, boleta, sal))
+        }
+
+        fn duenio_nft(&self, coleccion:AccountId, nft:Id)->Option<AccountId>{
+            let psp34: ink::contract_ref!(PSP34) = coleccion.into();
+            match psp34.call().owner_of(nft).try_invoke() {
+                Ok(Ok(duenio)) => duenio,
+                _ => panic!("NO SE PUDO CONSULTAR LA COLECCION DE NFT"),
+            }
+        }
+
+        //Chequea que la votacion este abierta y que la cuenta pueda votar y todavia no lo haya hecho. Si la votacion es parte de un grupo de boletas, el padron es el de la votacion principal del grupo
+        //Los requisitos ya se aplicaron al congelar el padron, que se congela antes de votar. Devuelve false si la cuenta no es un usuario registrado o no esta en el padron
+        fn puede_votar(&self, pos:usize, caller:AccountId, momento:Timestamp)->bool{
+            let v = &self.votaciones[pos];
+            let padron = &self.votaciones[self.posicion_padron(pos)];
+            if let Some(us) = self.usuarios_reg.iter().find(|u| u.acc_id == caller){
+                if !v.inicio(momento){
+                    panic!("LA VOTACION TODAVIA NO INICIO");
+                }
+                if v.finalizo(momento){
+                    panic!("LA VOTACION FINALIZO");
+                }
+                if us.suspendido{
+                    panic!("TU USUARIO ESTA SUSPENDIDO");
+                }
+                if padron.esta_habilitado_para_votar(caller){
+                    if v.ya_voto(caller){
+                        panic!("YA VOTASTE EN ESTA VOTACION");
+                    }
+                    return true
+                }
+            }
+            false
+        }
+
+        fn mostrar_opciones(&self, v:&Votacion){
+            let mut x: i32  = 0;
+            ink::env::debug_println!("Votacion {}: {}",v.id,v.puesto);
+            if let TipoVotacion::Referendum(consulta) = &v.tipo{
+                ink::env::debug_println!("{}",consulta.pregunta);
+                consulta.opciones.iter().enumerate().for_each(|(i, o)| ink::env::debug_println!("Opcion {}: {}",i.wrapping_add(1),o));
+            }
+            ink::env::debug_println!("Candidatos");
+            v.candidatos.iter().for_each(|c|{
+                x = x.wrapping_add(1);
+                if let Some(us) =self.usuarios_reg.iter().find(|u|u.acc_id==*c){  //siempre va a entrar ya que si esta como candidato en la votacion si o si esta registrado 
+                    ink::env::debug_println!("Opcion {}: {} {}",x,us.nombre,us.apellido);
+                }
+            });
+        }
 
-            } else{
-                ink::env::debug_println!("No hay solicitudes para candidatos");
+        //Unicamente el administrador puede agrupar votaciones que se votan el mismo dia (mismas fechas) en un grupo de boletas. Todas usan el padron de la primera votacion de la lista
+        //Las demas no pueden tener votantes propios ni solicitudes de votante pendientes, y ninguna puede haber iniciado ni estar en otro grupo
+        #[ink(message)]
+        pub fn crear_grupo_boletas(&mut self, id_grupo:i32, ids_votaciones:Vec<i32>) {
+            self.crear_grupo_boletas_impl(id_grupo, ids_votaciones);
+        }
 
+        fn crear_grupo_boletas_impl(&mut self, id_grupo:i32, ids_votaciones:Vec<i32>){
+            let caller = self.env().caller();
+            let momento = self.env().block_timestamp();
+            if caller != self.admin {
+                panic!("SOLO EL ADMINISTRADOR PUEDE CREAR GRUPOS DE BOLETAS");
+            }
+            if self.grupos_boletas.iter().any(|g| g.id == id_grupo){
+                panic!("YA EXISTE UN GRUPO DE BOLETAS CON ID: {}",id_grupo);
+            }
+            if ids_votaciones.len() < 2{
+                panic!("UN GRUPO DE BOLETAS TIENE QUE TENER AL MENOS DOS VOTACIONES");
             }
+            let principal = ids_votaciones[0];
+            let posiciones: Vec<usize> = ids_votaciones.iter().map(|id| {
+                self.votaciones.iter().position(|v| v.id == *id).unwrap_or_else(|| panic!("NO EXISTE VOTACION DE ID: {}",id))
+            }).collect();
+            let (inicio, fin) = (self.votaciones[posiciones[0]].fecha_inicio, self.votaciones[posiciones[0]].fecha_fin);
+            posiciones.iter().enumerate().for_each(|(i, pos)| {
+                let v = &self.votaciones[*pos];
+                if ids_votaciones[..i].contains(&v.id){
+                    panic!("LA VOTACION {} ESTA REPETIDA",v.id);
+                }
+                if v.inicio(momento){
+                    panic!("LA VOTACION {} YA INICIO",v.id);
+                }
+                if v.fecha_inicio != inicio || v.fecha_fin != fin{
+                    panic!("LAS VOTACIONES DE UN GRUPO TIENEN QUE TENER LAS MISMAS FECHAS");
+                }
+                if v.padron_de.is_some() || self.grupos_boletas.iter().any(|g| g.votaciones.contains(&v.id)){
+                    panic!("LA VOTACION {} YA ESTA EN UN GRUPO",v.id);
+                }
+                if v.token.is_some() || v.coleccion_nft.is_some() || v.dias_revelacion.is_some() || v.anonima || v.custodia.is_some(){
+                    panic!("LA VOTACION {} SE VOTA CON TOKEN, NFT, EN SECRETO, ANONIMA O CIFRADA Y NO SE PUEDE AGRUPAR",v.id);
+                }
+                if i > 0 && (!v.votantes.is_empty() || self.espera_votantes.iter().any(|e| e.1 == v.id)){
+                    panic!("LA VOTACION {} YA TIENE VOTANTES PROPIOS",v.id);
+                }
+                let p = &self.votaciones[posiciones[0]];
+                if v.modo_inscripcion != p.modo_inscripcion || !v.requisitos.mismos_votantes(&p.requisitos){  // las demas usan el padron de la principal
+                    panic!("LA VOTACION {} PIDE OTROS REQUISITOS O OTRO MODO DE INSCRIPCION A LOS VOTANTES",v.id);
+                }
+            });
+            posiciones[1..].iter().for_each(|pos| self.votaciones[*pos].padron_de = Some(principal));
+            self.grupos_boletas.push(GrupoBoletas{ id:id_grupo, votaciones:ids_votaciones });
         }
 
-        //Unicamente el administrador puede validar o rechazar votantes para las votaciones, siempre y cuando esta votacion no haya comenzado
+        //Vota en todas las votaciones del grupo en una sola transaccion, con una boleta por votacion. Si alguna boleta es invalida no se registra ninguna
         #[ink(message)]
-        pub fn validar_votante(&mut self, aceptar: bool) {
-            self.validar_votante_impl(aceptar);
+        pub fn votar_grupo(&mut self, id_grupo:i32, boletas:Vec<(i32,Boleta)>) {
+            self.votar_grupo_impl(id_grupo, boletas);
         }
 
-        fn validar_votante_impl(&mut self, aceptar:bool){
-            let mut aux: Option<String> = None;
-            let mut vot_id=0;
+        fn votar_grupo_impl(&mut self, id_grupo:i32, boletas:Vec<(i32,Boleta)>){
             let caller = self.env().caller();
             let momento = self.env().block_timestamp();
-            if caller == self.admin {
-                if !self.espera_votantes.is_empty() {
-                    let acc_id = self.espera_votantes[0].0;
-                    vot_id = self.espera_votantes[0].1;
-                    
-                    self.usuarios_reg.iter_mut().for_each(|u| { //si ya estas en la espera de candidatos, si o si vas a estar en el vector de usuarios 
-                        if u.acc_id == acc_id {
-                            let mut s1 = u.nombre.clone();
-                            let s2 = String::from(" ");
-                            let s3 = u.apellido.clone();
-                            s1.push_str(&s2);
-                            s1.push_str(&s3);
-                            aux = Some(s1); 
-                            if let Some(vot) = self.votaciones.iter_mut().find(|v| v.id == vot_id){
-                                if vot.inicio(momento){
-                                    self.espera_votantes.remove(0);
-                                }
-                                if aceptar{
-                                    vot.sumar_votante(acc_id);
-                                }
-                            }
-                            self.espera_votantes.remove(0);
-                        }
-                    })
-                }
+            let ids = if let Some(g) = self.grupos_boletas.iter().find(|g| g.id == id_grupo){
+                g.votaciones.clone()
             }else{
-                panic!("SOLO EL ADMINISTRADOR PUEDE VALIDAR VOTANTES");
+                panic!("NO EXISTE GRUPO DE BOLETAS CON ID: {}",id_grupo);
+            };
+            if boletas.len() != ids.len() || !ids.iter().all(|id| boletas.iter().filter(|b| b.0 == *id).count() == 1){
+                panic!("TIENE QUE HABER EXACTAMENTE UNA BOLETA POR CADA VOTACION DEL GRUPO");
             }
-            if let Some(a) =aux{
-                ink::env::debug_println!("Aceptar solicitud de votante del usuario {:?} para la votacion de id {}",a,vot_id);
-
-            } else{
-                ink::env::debug_println!("No hay solicitudes para votante");
-
+            self.congelar_padron_si_inicio(ids[0], momento);
+            let principal = self.votaciones.iter().position(|v| v.id == ids[0]).unwrap_or_else(|| panic!("NO EXISTE VOTACION DE ID: {}",ids[0]));
+            if !self.puede_votar(principal, caller, momento){  // un solo chequeo de habilitacion para todo el grupo
+                panic!("NO ESTAS HABILITADO PARA VOTAR EN ESTE GRUPO");
+            }
+            let us = self.usuarios_reg.iter().find(|u| u.acc_id == caller).cloned();
+            let mut registradas: Vec<(usize, Votacion)> = Vec::new();  // se suman sobre copias y se guardan solo si todas las boletas son validas
+            for (id, boleta) in boletas {
+                if let Some(pos) = self.votaciones.iter().position(|v| v.id == id){
+                    let mut v = self.votaciones[pos].clone();
+                    if v.ya_voto(caller){
+                        panic!("YA VOTASTE EN LA VOTACION {}",id);
+                    }
+                    if let Some(us) = &us{
+                        v.registrar_boleta(boleta, us);
+                    }
+                    v.votaron.push(caller);
+                    registradas.push((pos, v));
+                }
             }
+            registradas.into_iter().for_each(|(pos, v)| self.votaciones[pos] = v);
         }
 
+        //Si la votacion usa el padron de otra (por estar en un grupo de boletas), devuelve la posicion de esa otra
+        fn posicion_padron(&self, pos:usize)->usize{
+            self.votaciones[pos].padron_de.and_then(|id| self.votaciones.iter().position(|v| v.id == id)).unwrap_or(pos)
+        }
 
-        //El votante puede votar validando su identidad (debe estar registrado y validado por el administrador) 
+        //Cualquiera puede cerrar una votacion que ya termino. Se calcula el resultado segun el tipo de votacion y queda guardado
         #[ink(message)]
-        pub fn votar(&mut self, id_de_votacion: i32, opcion:i32) {
-            self.votar_impl(id_de_votacion, opcion);
+        pub fn finalizar_votacion(&mut self, id_de_votacion:i32) {
+            self.finalizar_votacion_impl(id_de_votacion);
         }
-        
-        fn votar_impl(&mut self,id_de_votacion:i32,opcion:i32){
-            let caller = self.env().caller();
-            let mut x: i32  = 0;
+
+        fn finalizar_votacion_impl(&mut self, id_de_votacion:i32){
             let momento = self.env().block_timestamp();
-            if caller != self.admin{
-                if self.usuarios_reg.iter().any(|u| u.acc_id == caller){
-                    if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){
-                        if !v.inicio(momento){
-                            panic!("LA VOTACION TODAVIA NO INICIO");
-                        }
-                        if v.finalizo(momento){
-                            panic!("LA VOTACION FINALIZO");
-                        }
-                        if v.es_votante(caller){ //Los candidatos de una votacion no van a poder votar en esa misma ya que no van a estar registrados como votantes 
-                            ink::env::debug_println!("Candidatos");
-                            v.candidatos.iter().for_each(|c|{
-                                x = x.wrapping_add(1);
-                                if let Some(us) =self.usuarios_reg.iter().find(|u|u.acc_id==*c){  //siempre va a entrar ya que si esta como candidato en la votacion si o si esta registrado 
-                                    ink::env::debug_println!("Opcion {}: {} {}",x,us.nombre,us.apellido);
-                                }
-                                
-                            });
-                            if let Some(op) = opcion.checked_sub(1) {
-                                if op as usize <= v.candidatos.len() {
-                                    v.sumar_voto(op as usize);
-                                }
-                            }
-                        }
-                    }else{
-                        panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
-                    }
+            self.congelar_padron_si_inicio(id_de_votacion, momento);  // por si nadie voto
+            if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id_de_votacion){
+                if !v.finalizo(momento){
+                    panic!("LA VOTACION TODAVIA NO FINALIZO");
+                }
+                if momento <= v.fin_revelacion(){  // si no es secreta es la misma fecha de fin
+                    panic!("TODAVIA SE PUEDEN REVELAR VOTOS");
                 }
+                if v.resultado.is_some(){
+                    panic!("LA VOTACION YA FUE FINALIZADA");
+                }
+                let descifrado = if v.custodia.is_some() { Some(v.descifrar_boletas()) } else { None };
+                let mut resultado = v.calcular_resultado();
+                resultado.descifrado = descifrado;
+                ink::env::debug_println!("Resultado de la votacion {}: {:?}",id_de_votacion,resultado);
+                v.resultado = Some(resultado);
+            }else{
+                panic!("NO EXISTE VOTACION DE ID: {}",id_de_votacion);
             }
+        }
 
+        #[ink(message)]
+        pub fn get_resultado(&self, id:i32)->Option<Resultado>{
+            self.votaciones.iter().find(|v| v.id == id).and_then(|v| v.resultado.clone())
         }
 
         #[ink(message)]
@@ -487,6 +2790,9 @@ mod sistema {
             
             let mut x: i32=0;
             if let Some(v) = self.votaciones.iter_mut().find(|vot| vot.id == id){
+                    if let TipoVotacion::Referendum(consulta) = &v.tipo{
+                        consulta.opciones.iter().zip(v.votos_opciones.iter()).for_each(|(o, cant)| ink::env::debug_println!("{}: {} votos",o,cant));
+                    }
                     ink::env::debug_println!("Candidatos y sus votos actuales");
                     v.candidatos.iter().for_each(|c|{
                         x = x.wrapping_add(1);
@@ -521,6 +2827,12 @@ mod sistema {
             x as i32
         }
 
+        #[ink(message)]
+        pub fn get_cant_espera_cambios_perfil(&self)->i32{
+            let x =self.espera_cambios_perfil.len();
+            x as i32
+        }
+
         #[ink(message)]
         pub fn get_cant_candidatos_vot(&self,id:i32)->i32{
             if let Some(vot) =self.votaciones.iter().find(|v| v.id == id){
@@ -531,12 +2843,41 @@ mod sistema {
 
         #[ink(message)]
         pub fn get_cant_votantes_vot(&self,id:i32)->i32{
-            if let Some(vot) =self.votaciones.iter().find(|v| v.id == id){
-                return vot.get_cant_votantes_vot();
+            if let Some(pos) =self.votaciones.iter().position(|v| v.id == id){
+                return self.cant_votantes_habilitados(&self.votaciones[self.posicion_padron(pos)]);
             }
             0
         }
 
+        //Devuelve (cantidad que voto, cantidad habilitada para votar)
+        #[ink(message)]
+        pub fn get_participacion(&self,id:i32)->(i32,i32){
+            if let Some(pos) =self.votaciones.iter().position(|v| v.id == id){
+                let votaron = self.votaciones[pos].votaron.len().saturating_add(self.votaciones[pos].anuladores.len());  // en una votacion anonima solo se conocen los anuladores
+                return (votaron as i32, self.cant_votantes_habilitados(&self.votaciones[self.posicion_padron(pos)]));
+            }
+            (0,0)
+        }
+
+        //En una votacion abierta sin congelar el total son los usuarios que podrian votar a la fecha de inicio, en las demas es la lista de votantes
+        fn cant_votantes_habilitados(&self, vot:&Votacion)->i32{
+            match vot.modo_inscripcion {
+                ModoInscripcion::Abierta if !vot.padron_congelado() => self.padron_abierto(vot).len() as i32,
+                _ => vot.get_cant_votantes_vot(),
+            }
+        }
+
+
+        #[ink(message)]
+        pub fn get_hash_padron_congelado(&self,id:i32)->Option<[u8; 32]>{
+            self.votaciones.iter().position(|v| v.id == id).and_then(|pos| self.votaciones[self.posicion_padron(pos)].hash_padron_congelado)
+        }
+
+        #[ink(message)]
+        pub fn get_requisitos_votacion(&self,id:i32)->Option<Requisitos>{
+            self.votaciones.iter().find(|v| v.id == id).map(|v| v.requisitos.clone())
+        }
+
 
         #[ink(message)]
         pub fn get_id_posicion(&self, pos:i32)->AccountId{
@@ -544,11 +2885,798 @@ mod sistema {
         }
 
         
+        #[ink(message)]
+        pub fn get_cant_invitaciones(&self)->i32{
+            let x =self.invitaciones.len();
+            x as i32
+        }
+
+        #[ink(message)]
+        pub fn get_oraculo(&self) -> Option<AccountId> {
+            self.oraculo
+        }
+
+        //Cuanto pesa el voto de la cuenta en la votacion (en las que ponderan por token, el saldo de la foto, que se consulta cuando vota)
+        #[ink(message)]
+        pub fn get_peso_voto(&self, id:i32, acc_id:AccountId) -> u128 {
+            self.votaciones.iter().find(|v| v.id == id).map(|v| v.peso_de(acc_id)).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn get_clave_publica(&self, id:i32) -> Option<[u8; 32]> {
+            self.votaciones.iter().find(|v| v.id == id).and_then(|v| v.clave_publica)
+        }
+
+        //Claves de una votacion anonima con las que todavia se puede votar, para armar el anillo
+        #[ink(message)]
+        pub fn get_claves_anonimas(&self, id:i32) -> Vec<[u8; 32]> {
+            self.votaciones.iter().find(|v| v.id == id).map(|v| self.claves_habilitadas(v).into_iter().collect()).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_grupo_boletas(&self, id_grupo:i32) -> Option<Vec<i32>> {
+            self.grupos_boletas.iter().find(|g| g.id == id_grupo).map(|g| g.votaciones.clone())
+        }
+
         #[ink(message)]
         pub fn get_owner_id(&self) -> AccountId {
             self.admin
         }
     }
 
-   
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn cuenta(n:u8)->AccountId{
+            AccountId::from([n; 32])
+        }
+
+        fn configuracion(modo_inscripcion:ModoInscripcion, tipo:TipoVotacion)->ConfiguracionVotacion{
+            let requisitos = Requisitos{ edad_minima_candidato:18, edad_minima_votante:18, categorias:None, candidatos_pueden_votar:false };
+            ConfiguracionVotacion{ requisitos, modo_inscripcion, tipo, claustros:None, token:None, coleccion_nft:None, dias_revelacion:None, anonima:false, custodia:None }
+        }
+
+        fn votacion_de(tipo:TipoVotacion, cant_candidatos:u8)->Votacion{
+            let mut v = Votacion::new(1, String::from("Puesto"), configuracion(ModoInscripcion::Abierta, tipo), 0, 1);
+            (1..=cant_candidatos).for_each(|n| v.sumar_candidato(cuenta(n)));
+            v
+        }
+
+        //Carga `veces` boletas iguales con las posiciones de los candidatos en orden de preferencia
+        fn cargar_rankings(v:&mut Votacion, boletas:&[(u32, &[u8])]){
+            boletas.iter().for_each(|(veces, ranking)| (0..*veces).for_each(|_| v.boletas_ranking.push(ranking.to_vec())));
+        }
+
+        #[ink::test]
+        fn segunda_vuelta_transfiere_los_votos_del_eliminado(){
+            let mut v = votacion_de(TipoVotacion::SegundaVueltaInstantanea, 3);
+            cargar_rankings(&mut v, &[(4, &[0]), (3, &[1]), (2, &[2, 1])]);
+            let (ganador, rondas) = v.calcular_segunda_vuelta();
+            assert_eq!(ganador, Some(cuenta(2)));
+            assert_eq!(rondas.len(), 2);
+            assert_eq!(rondas[0].conteo, vec![(cuenta(1), 4), (cuenta(2), 3), (cuenta(3), 2)]);
+            assert_eq!(rondas[0].eliminado, Some(cuenta(3)));
+            assert_eq!(rondas[0].transferencias, vec![(cuenta(2), 2)]);
+            assert_eq!(rondas[1].conteo, vec![(cuenta(1), 4), (cuenta(2), 5)]);
+            assert_eq!(rondas[1].eliminado, None);
+        }
+
+        #[ink::test]
+        fn segunda_vuelta_no_cuenta_las_boletas_agotadas(){
+            let mut v = votacion_de(TipoVotacion::SegundaVueltaInstantanea, 3);
+            cargar_rankings(&mut v, &[(4, &[0]), (3, &[1]), (2, &[2])]);
+            let (ganador, rondas) = v.calcular_segunda_vuelta();
+            assert_eq!(ganador, Some(cuenta(1)));  // 4 de 7 boletas no agotadas
+            assert!(rondas[0].transferencias.is_empty());
+            assert_eq!(rondas[1].agotadas, 2);
+            assert_eq!(rondas[1].conteo, vec![(cuenta(1), 4), (cuenta(2), 3)]);
+        }
+
+        #[ink::test]
+        fn segunda_vuelta_elimina_al_ultimo_postulado_en_un_empate(){
+            let mut v = votacion_de(TipoVotacion::SegundaVueltaInstantanea, 3);
+            cargar_rankings(&mut v, &[(2, &[0]), (1, &[1]), (1, &[2, 0])]);
+            let (ganador, rondas) = v.calcular_segunda_vuelta();
+            assert_eq!(rondas[0].eliminado, Some(cuenta(3)));
+            assert_eq!(rondas[0].transferencias, vec![(cuenta(1), 1)]);
+            assert_eq!(ganador, Some(cuenta(1)));
+        }
+
+        #[ink::test]
+        fn segunda_vuelta_sin_ganador_si_todos_empatan(){
+            let mut v = votacion_de(TipoVotacion::SegundaVueltaInstantanea, 2);
+            cargar_rankings(&mut v, &[(1, &[0, 1]), (1, &[1, 0])]);
+            let (ganador, rondas) = v.calcular_segunda_vuelta();
+            assert_eq!(ganador, None);
+            assert_eq!(rondas.len(), 1);
+            assert_eq!(rondas[0].eliminado, None);
+        }
+
+        fn bancas_de(detalle:DetalleResultado)->Vec<(AccountId,u32)>{
+            match detalle {
+                DetalleResultado::Proporcional{ bancas, .. } => bancas,
+                _ => panic!("SE ESPERABA UN REPARTO PROPORCIONAL"),
+            }
+        }
+
+        fn conteo_listas()->Vec<(AccountId,u128)>{
+            vec![(cuenta(1), 100_000), (cuenta(2), 80_000), (cuenta(3), 30_000), (cuenta(4), 20_000)]
+        }
+
+        #[ink::test]
+        fn reparto_dhondt(){
+            let detalle = Votacion::calcular_reparto(&conteo_listas(), 8, &MetodoReparto::DHondt, 0);
+            let DetalleResultado::Proporcional{ bancas, cocientes } = detalle else { panic!("SE ESPERABA UN REPARTO PROPORCIONAL") };
+            assert_eq!(bancas, vec![(cuenta(1), 4), (cuenta(2), 3), (cuenta(3), 1)]);
+            assert_eq!(cocientes.len(), 4);
+            assert_eq!(cocientes[0].1[..3], [100_000_000, 50_000_000, 33_333_333]);
+        }
+
+        #[ink::test]
+        fn reparto_sainte_lague(){
+            let bancas = bancas_de(Votacion::calcular_reparto(&conteo_listas(), 8, &MetodoReparto::SainteLague, 0));
+            assert_eq!(bancas, vec![(cuenta(1), 3), (cuenta(2), 3), (cuenta(3), 1), (cuenta(4), 1)]);
+        }
+
+        #[ink::test]
+        fn reparto_deja_afuera_a_las_listas_bajo_el_umbral(){
+            let detalle = Votacion::calcular_reparto(&conteo_listas(), 8, &MetodoReparto::SainteLague, 10);  // la cuarta tiene menos del 9%
+            let DetalleResultado::Proporcional{ bancas, cocientes } = detalle else { panic!("SE ESPERABA UN REPARTO PROPORCIONAL") };
+            assert_eq!(bancas, vec![(cuenta(1), 4), (cuenta(2), 3), (cuenta(3), 1)]);
+            assert!(cocientes.iter().all(|c| c.0 != cuenta(4)));
+        }
+
+        #[ink::test]
+        fn reparto_desempata_por_votos_y_despues_por_orden(){
+            let conteo = vec![(cuenta(1), 60), (cuenta(2), 30)];
+            let bancas = bancas_de(Votacion::calcular_reparto(&conteo, 2, &MetodoReparto::DHondt, 0));
+            assert_eq!(bancas, vec![(cuenta(1), 2)]);  // 60/2 empata con 30/1 y gana la de mas votos
+
+            let conteo = vec![(cuenta(1), 50), (cuenta(2), 50), (cuenta(3), 0)];
+            let bancas = bancas_de(Votacion::calcular_reparto(&conteo, 1, &MetodoReparto::DHondt, 0));
+            assert_eq!(bancas, vec![(cuenta(1), 1)]);
+        }
+
+        //Ejemplo de 45 votantes y 5 candidatos del articulo de Wikipedia sobre el metodo
+        #[ink::test]
+        fn schulze_ejemplo_de_45_votantes(){
+            let mut v = votacion_de(TipoVotacion::Schulze, 5);
+            cargar_rankings(&mut v, &[
+                (5, &[0, 2, 1, 4, 3]), (5, &[0, 3, 4, 2, 1]), (8, &[1, 4, 3, 0, 2]), (3, &[2, 0, 1, 4, 3]),
+                (7, &[2, 0, 4, 1, 3]), (2, &[2, 1, 0, 3, 4]), (7, &[3, 2, 4, 1, 0]), (8, &[4, 1, 0, 3, 2]),
+            ]);
+            let (ganador, detalle) = v.calcular_schulze();
+            let DetalleResultado::Schulze{ preferencias, caminos, ganador_condorcet, .. } = detalle else { panic!("SE ESPERABA UN DETALLE DE SCHULZE") };
+            assert_eq!(preferencias, vec![
+                vec![0, 20, 26, 30, 22], vec![25, 0, 16, 33, 18], vec![19, 29, 0, 17, 24], vec![15, 12, 28, 0, 14], vec![23, 27, 21, 31, 0],
+            ]);
+            assert_eq!(caminos, vec![
+                vec![0, 28, 28, 30, 24], vec![25, 0, 28, 33, 24], vec![25, 29, 0, 29, 24], vec![25, 28, 28, 0, 24], vec![25, 28, 28, 31, 0],
+            ]);
+            assert_eq!(ganador, Some(cuenta(5)));
+            assert_eq!(ganador_condorcet, None);
+        }
+
+        //Los que no figuran en un ranking parcial quedan detras de todos los que figuran y empatados entre si
+        #[ink::test]
+        fn schulze_con_rankings_parciales_y_ganador_condorcet(){
+            let mut v = votacion_de(TipoVotacion::Schulze, 3);
+            cargar_rankings(&mut v, &[(2, &[1]), (1, &[0, 2])]);
+            let (ganador, detalle) = v.calcular_schulze();
+            let DetalleResultado::Schulze{ preferencias, ganador_condorcet, .. } = detalle else { panic!("SE ESPERABA UN DETALLE DE SCHULZE") };
+            assert_eq!(preferencias, vec![vec![0, 1, 1], vec![2, 0, 2], vec![0, 1, 0]]);
+            assert_eq!(ganador, Some(cuenta(2)));
+            assert_eq!(ganador_condorcet, Some(cuenta(2)));
+        }
+
+        #[ink::test]
+        fn schulze_sin_ganador_si_hay_empate(){
+            let mut v = votacion_de(TipoVotacion::Schulze, 2);
+            cargar_rankings(&mut v, &[(1, &[0, 1]), (1, &[1, 0])]);
+            assert_eq!(v.calcular_schulze().0, None);
+            assert_eq!(votacion_de(TipoVotacion::Schulze, 2).calcular_schulze().0, None);
+        }
+
+        //Cada claustro reparte su peso entre los candidatos segun los votos dentro del claustro, sin importar cuantos votaron
+        #[ink::test]
+        fn ponderacion_por_claustros(){
+            let mut v = votacion_de(TipoVotacion::Mayoria, 2);
+            assert!(v.calcular_ponderacion().is_none());
+            v.claustros = Some(vec![
+                Claustro{ categoria:String::from("Docentes"), peso:60 },
+                Claustro{ categoria:String::from("Estudiantes"), peso:40 },
+                Claustro{ categoria:String::from("Nodocentes"), peso:10 },
+            ]);
+            v.votos_claustro = vec![
+                (1, BTreeMap::from([(cuenta(1), 1)])),
+                (4, BTreeMap::from([(cuenta(1), 1), (cuenta(2), 3)])),
+                (0, BTreeMap::new()),
+            ];
+            let ponderacion = v.calcular_ponderacion().unwrap();
+            assert_eq!(ponderacion.conteo, vec![(cuenta(1), 700_000), (cuenta(2), 300_000)]);  // 60 + 40/4 y 40*3/4, en diezmilesimas
+            assert_eq!(ponderacion.claustros[1].conteo, vec![(cuenta(2), 3), (cuenta(1), 1)]);
+            assert_eq!(ponderacion.claustros[2].votaron, 0);
+        }
+
+        //El saldo de la foto es el de la cuenta original aunque el votante migre de cuenta antes de votar, y el que ya voto se lleva su saldo
+        #[ink::test]
+        fn migrar_cuenta_conserva_el_titular_del_saldo(){
+            let mut v = votacion_de(TipoVotacion::Mayoria, 1);
+            v.token = Some(cuenta(9));
+            v.migrar_cuenta(cuenta(10), cuenta(11));
+            v.migrar_cuenta(cuenta(11), cuenta(12));
+            assert_eq!(v.titulares_saldo.get(&cuenta(12)), Some(&cuenta(10)));
+            assert!(!v.titulares_saldo.contains_key(&cuenta(11)));
+
+            v.saldos.insert(cuenta(20), 500);
+            v.migrar_cuenta(cuenta(20), cuenta(21));
+            assert_eq!(v.peso_de(cuenta(21)), 500);
+            assert!(!v.titulares_saldo.contains_key(&cuenta(21)));
+        }
+
+        fn fecha(dia:u32, mes:u32, anio:i32)->Fecha{
+            Fecha{ dia, mes, anio }
+        }
+
+        fn clave_publica(privada:&Scalar)->[u8; 32]{
+            RistrettoPoint::mul_base(privada).compress().to_bytes()
+        }
+
+        //Lo que hace el votante fuera de la cadena: firma escondido en la posicion `pos` del anillo, con los aleatorios sacados de un hash para que la prueba sea reproducible
+        fn firmar_anillo(mensaje:[u8; 32], privada:Scalar, anillo:Vec<[u8; 32]>, pos:usize)->FirmaAnillo{
+            let g = RISTRETTO_BASEPOINT_POINT;
+            let n = anillo.len();
+            let aleatorio = |k:usize| Scalar::from_bytes_mod_order_wide(&hash_ancho(&(b"prueba", mensaje, k as u32)));
+            let imagen = privada * hash_a_punto(&anillo[pos]);
+            let alfa = aleatorio(n);
+            let mut desafios: Vec<Scalar> = (0..n).map(|_| Scalar::ZERO).collect();
+            let mut respuestas: Vec<Scalar> = (0..n).map(|_| Scalar::ZERO).collect();
+            desafios[(pos + 1) % n] = desafio_anillo(mensaje, &(alfa * g), &(alfa * hash_a_punto(&anillo[pos])));
+            let mut i = (pos + 1) % n;
+            while i != pos {
+                respuestas[i] = aleatorio(i);
+                let l = respuestas[i] * g + desafios[i] * punto_de(anillo[i]).unwrap();
+                let r = respuestas[i] * hash_a_punto(&anillo[i]) + desafios[i] * imagen;
+                desafios[(i + 1) % n] = desafio_anillo(mensaje, &l, &r);
+                i = (i + 1) % n;
+            }
+            respuestas[pos] = alfa - desafios[pos] * privada;
+            FirmaAnillo{ anillo, imagen:imagen.compress().to_bytes(), desafio:desafios[0].to_bytes(), respuestas:respuestas.iter().map(|r| r.to_bytes()).collect() }
+        }
+
+        #[ink::test]
+        fn firma_de_anillo_enlazable(){
+            let privadas: Vec<Scalar> = (1..=3u64).map(|k| Scalar::from(k * 1_000_003)).collect();
+            let claves: Vec<[u8; 32]> = privadas.iter().map(clave_publica).collect();
+            let mensaje = [7u8; 32];
+
+            let firma = firmar_anillo(mensaje, privadas[1], claves.clone(), 1);
+            assert!(verificar_firma_anillo(mensaje, &firma));
+            assert!(!verificar_firma_anillo([8u8; 32], &firma));  // la firma es de la boleta, no se puede cambiar
+
+            let otra = firmar_anillo(mensaje, privadas[1], vec![claves[2], claves[1]], 1);
+            assert!(verificar_firma_anillo(mensaje, &otra));
+            assert_eq!(otra.imagen, firma.imagen);  // la misma clave da la misma imagen en cualquier anillo
+            assert_ne!(firmar_anillo(mensaje, privadas[0], claves.clone(), 0).imagen, firma.imagen);
+
+            let ajena = firmar_anillo(mensaje, Scalar::from(5u64), claves.clone(), 1);  // el que firma no es dueño de ninguna clave del anillo
+            assert!(!verificar_firma_anillo(mensaje, &ajena));
+            let mut cambiada = firma.clone();
+            cambiada.imagen = (privadas[0] * hash_a_punto(&claves[0])).compress().to_bytes();  // no se puede votar de nuevo poniendo otra imagen
+            assert!(!verificar_firma_anillo(mensaje, &cambiada));
+        }
+
+        //Crea una votacion anonima con bob de candidato y registra una clave por cada una de las demas cuentas
+        fn votacion_anonima_con_claves(cuentas_votantes:&[AccountId])->(Sistema, Vec<Scalar>){
+            let cuentas = cuentas_de_prueba();
+            let mut usuarios = vec![cuentas.bob];
+            usuarios.extend_from_slice(cuentas_votantes);
+            let mut sistema = sistema_con_usuarios(&usuarios);
+            let mut config = configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria);
+            config.anonima = true;
+            crear_votacion_con_candidatos(&mut sistema, 1, config, &[cuentas.bob]);
+            let privadas: Vec<Scalar> = (1..=cuentas_votantes.len() as u64).map(|k| Scalar::from(k * 1_000_003)).collect();
+            for (acc, privada) in cuentas_votantes.iter().zip(privadas.iter()) {
+                llamar_desde(*acc);
+                sistema.registrar_clave_anonima(1, clave_publica(privada));
+            }
+            llamar_desde(cuentas.alice);
+            (sistema, privadas)
+        }
+
+        #[ink::test]
+        fn votacion_anonima(){
+            let cuentas = cuentas_de_prueba();
+            let (mut sistema, privadas) = votacion_anonima_con_claves(&[cuentas.charlie, cuentas.django, cuentas.eve]);
+            let anillo = sistema.get_claves_anonimas(1);
+            assert_eq!(anillo.len(), 3);
+            let pos = anillo.iter().position(|c| *c == clave_publica(&privadas[0])).unwrap();
+
+            en_momento(DURANTE);
+            llamar_desde(cuentas.frank);  // vota desde una cuenta que no tiene nada que ver con la que registro la clave
+            let mensaje = sistema.mensaje_anonimo(1, &Boleta::Simple(1));
+            sistema.votar_anonimo(1, Boleta::Simple(1), firmar_anillo(mensaje, privadas[0], anillo.clone(), pos));
+            assert_eq!(sistema.get_participacion(1).0, 1);
+
+            let repetido = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let mensaje = sistema.mensaje_anonimo(1, &Boleta::Simple(2));
+                sistema.votar_anonimo(1, Boleta::Simple(2), firmar_anillo(mensaje, privadas[0], anillo.clone(), pos));
+            }));
+            assert!(repetido.is_err());  // la misma clave da la misma imagen
+            let otra = if pos == 0 { 1 } else { 0 };  // una clave de las dos primeras que todavia no voto
+            let privada = *privadas.iter().find(|p| clave_publica(p) == anillo[otra]).unwrap();
+            let anillo_corto = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                sistema.votar_anonimo(1, Boleta::Simple(1), firmar_anillo(mensaje, privada, anillo[..2].to_vec(), otra));
+            }));
+            assert!(anillo_corto.is_err());  // con menos de ANILLO_MINIMO claves registradas el anillo tiene que tener todas
+            let otra_boleta = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                sistema.votar_anonimo(1, Boleta::Simple(2), firmar_anillo(mensaje, privadas[1], anillo.clone(), (pos + 1) % 3));
+            }));
+            assert!(otra_boleta.is_err());  // la firma es de otra boleta
+
+            en_momento(DESPUES_DEL_FIN);
+            sistema.finalizar_votacion(1);
+            assert_eq!(sistema.get_resultado(1).unwrap().conteo[0].1, 1);
+        }
+
+        #[ink::test]
+        fn migrar_cuenta_lleva_el_registro_anonimo(){
+            let mut v = votacion_de(TipoVotacion::Mayoria, 1);
+            v.claves_anonimas.insert(cuenta(10), [1u8; 32]);
+            v.migrar_cuenta(cuenta(10), cuenta(11));
+            assert_eq!(v.claves_anonimas.get(&cuenta(11)), Some(&[1u8; 32]));
+            assert!(!v.claves_anonimas.contains_key(&cuenta(10)));
+        }
+
+        //Los pares se ordenan antes de hashearlos, asi la prueba no dice de que lado va cada hermano
+        #[ink::test]
+        fn camino_de_merkle(){
+            let (hoja, a, b) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+            assert_eq!(Votacion::hash_par(hoja, a), Votacion::hash_par(a, hoja));
+            let mut par = [0u8; 64];
+            par[..32].copy_from_slice(&hoja);
+            par[32..].copy_from_slice(&a);
+            let mut esperado = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&par, &mut esperado);
+            assert_eq!(Votacion::hash_par(a, hoja), esperado);
+            assert_eq!(Votacion::raiz_desde(hoja, &[a, b]), Votacion::hash_par(b, esperado));
+            assert_eq!(Votacion::raiz_desde(hoja, &[]), hoja);
+
+            let mut v = votacion_de(TipoVotacion::Mayoria, 1);
+            assert!(!v.esta_en_padron(hoja, &[a, b]));
+            v.padron = Some(Votacion::raiz_desde(b, &[esperado]));
+            assert!(v.esta_en_padron(hoja, &[a, b]));
+            assert!(!v.esta_en_padron(a, &[hoja, a]));
+        }
+
+        //Reparto de Shamir de 2 de 3 con f(x) = 123456789 + 987654321*x
+        fn partes_de_prueba()->Vec<Scalar>{
+            (1..=3u64).map(|x| Scalar::from(123_456_789u64) + Scalar::from(987_654_321u64) * Scalar::from(x)).collect()
+        }
+
+        fn votacion_cifrada()->Votacion{
+            let mut v = votacion_de(TipoVotacion::Mayoria, 3);
+            v.custodia = Some(Custodia{ custodios:vec![cuenta(21), cuenta(22), cuenta(23)], umbral:2 });
+            v.compromisos_custodios = vec![None, None, None];
+            v.partes_clave = vec![None, None, None];
+            v
+        }
+
+        fn cifrar(clave:[u8; 32], opcion:u64, r:u64)->([u8; 32],[u8; 32]){
+            let (r, clave) = (Scalar::from(r), punto_de(clave).unwrap());
+            (RistrettoPoint::mul_base(&r).compress().to_bytes(), (RistrettoPoint::mul_base(&Scalar::from(opcion)) + r * clave).compress().to_bytes())
+        }
+
+        #[ink::test]
+        fn clave_publica_de_los_custodios(){
+            let partes = partes_de_prueba();
+            let mut v = votacion_cifrada();
+            v.compromisos_custodios = partes.iter().map(|p| Some(RistrettoPoint::mul_base(p).compress().to_bytes())).collect();
+            v.compromisos_custodios[2] = None;
+            v.calcular_clave_publica();
+            assert_eq!(v.clave_publica, None);  // faltan compromisos
+
+            v.compromisos_custodios[2] = Some(RistrettoPoint::mul_base(&(partes[2] + Scalar::ONE)).compress().to_bytes());
+            v.calcular_clave_publica();
+            assert_eq!(v.clave_publica, None);  // el tercero no cae en la recta de los dos primeros
+
+            v.compromisos_custodios[2] = Some(RistrettoPoint::mul_base(&partes[2]).compress().to_bytes());
+            v.calcular_clave_publica();
+            assert_eq!(v.clave_publica, Some(RistrettoPoint::mul_base(&Scalar::from(123_456_789u64)).compress().to_bytes()));
+        }
+
+        #[ink::test]
+        fn descifrar_boletas_con_umbral_de_partes(){
+            let partes = partes_de_prueba();
+            let mut v = votacion_cifrada();
+            v.compromisos_custodios = partes.iter().map(|p| Some(RistrettoPoint::mul_base(p).compress().to_bytes())).collect();
+            v.calcular_clave_publica();
+            let clave = v.clave_publica.unwrap();
+            v.boletas_cifradas = vec![cifrar(clave, 1, 11), cifrar(clave, 3, 12), cifrar(clave, 3, 13), cifrar(clave, 7, 14), ([0xff; 32], [0xff; 32])];
+            v.partes_clave[1] = Some(partes[1].to_bytes());
+            v.partes_clave[2] = Some(partes[2].to_bytes());
+            let descifrado = v.descifrar_boletas();
+            assert_eq!(descifrado.custodios, vec![cuenta(22), cuenta(23)]);
+            assert_eq!(descifrado.nulas, 2);  // la opcion 7 no existe y la ultima no es un punto
+            assert_eq!(v.conteo_ordenado(), vec![(cuenta(3), 2), (cuenta(1), 1), (cuenta(2), 0)]);
+        }
+
+        #[ink::test]
+        fn descifrar_boletas_registra_todas_las_partes(){
+            let partes = partes_de_prueba();
+            let mut v = votacion_cifrada();
+            v.compromisos_custodios = partes.iter().map(|p| Some(RistrettoPoint::mul_base(p).compress().to_bytes())).collect();
+            v.calcular_clave_publica();
+            let clave = v.clave_publica.unwrap();
+            v.boletas_cifradas = vec![cifrar(clave, 2, 11)];
+            v.partes_clave = partes.iter().map(|p| Some(p.to_bytes())).collect();
+            let descifrado = v.descifrar_boletas();
+            assert_eq!(descifrado.custodios, vec![cuenta(21), cuenta(22), cuenta(23)]);  // el tercero tambien publico aunque no hiciera falta
+            assert_eq!(v.conteo_ordenado()[0], (cuenta(2), 1));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "FALTAN PARTES DE LA CLAVE, HAY 1 DE 2")]
+        fn descifrar_boletas_sin_umbral_de_partes(){
+            let partes = partes_de_prueba();
+            let mut v = votacion_cifrada();
+            v.partes_clave[0] = Some(partes[0].to_bytes());
+            v.descifrar_boletas();
+        }
+
+        //Las votaciones de las pruebas con el sistema van del 1/1/2021 al 5/1/2021
+        const ANTES_DEL_INICIO: Timestamp = 1_600_000_000_000;
+        const DURANTE: Timestamp = 1_609_600_000_000;
+        const DESPUES_DEL_FIN: Timestamp = 1_610_000_000_000;
+
+        fn cuentas_de_prueba()->ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment>{
+            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn llamar_desde(acc:AccountId){
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(acc);
+        }
+
+        fn en_momento(momento:Timestamp){
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(momento);
+        }
+
+        fn falla(f:impl FnOnce())->bool{
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).is_err()
+        }
+
+        //Sistema administrado por alice con un usuario validado por cada cuenta (con dni 1, 2, ...), antes del inicio de las votaciones
+        fn sistema_con_usuarios(usuarios:&[AccountId])->Sistema{
+            llamar_desde(cuentas_de_prueba().alice);
+            en_momento(ANTES_DEL_INICIO);
+            let mut sistema = Sistema::new(String::from("Admin"));
+            for (dni, acc) in usuarios.iter().enumerate() {
+                llamar_desde(*acc);
+                sistema.registrar_usuario(String::from("Nombre"), String::from("Apellido"), fecha(1, 1, 1980), dni as i128 + 1, None);
+                llamar_desde(cuentas_de_prueba().alice);
+                sistema.validar_usuario(true, None);
+            }
+            sistema
+        }
+
+        //Crea la votacion y valida a los candidatos. Deja como llamador al administrador
+        fn crear_votacion_con_candidatos(sistema:&mut Sistema, id:i32, config:ConfiguracionVotacion, candidatos:&[AccountId]){
+            llamar_desde(cuentas_de_prueba().alice);
+            sistema.crear_votacion(id, String::from("Puesto"), fecha(1, 1, 2021), fecha(5, 1, 2021), config);
+            for acc in candidatos {
+                llamar_desde(*acc);
+                sistema.postularse_a_votacion(Rol::Candidato, id);
+                llamar_desde(cuentas_de_prueba().alice);
+                sistema.validar_candidato(true);
+            }
+        }
+
+        #[ink::test]
+        fn suspendido_no_puede_postularse_ni_votar(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie, cuentas.django]);
+            crear_votacion_con_candidatos(&mut sistema, 1, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.bob]);
+            crear_votacion_con_candidatos(&mut sistema, 2, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.charlie]);
+            assert_eq!(sistema.get_cant_candidatos_vot(2), 1);
+
+            sistema.suspender_usuario(cuentas.charlie, String::from("Registro fraudulento"));
+            assert_eq!(sistema.get_cant_candidatos_vot(2), 0);  // la votacion no habia iniciado
+            let registro = sistema.get_registro_suspensiones(Some(cuentas.charlie));
+            assert_eq!(registro.len(), 1);
+            assert_eq!(registro[0].accion, AccionSuspension::Suspension);
+            assert_eq!(registro[0].motivo, "Registro fraudulento");
+            assert_eq!(registro[0].dni, 2);
+            assert!(sistema.get_registro_suspensiones(Some(cuentas.django)).is_empty());
+
+            llamar_desde(cuentas.charlie);
+            assert!(falla(|| sistema.postularse_a_votacion(Rol::Candidato, 2)));
+            en_momento(DURANTE);
+            assert!(falla(|| sistema.votar(1, Boleta::Simple(1))));
+            llamar_desde(cuentas.django);
+            sistema.votar(1, Boleta::Simple(1));
+            en_momento(DESPUES_DEL_FIN);
+            llamar_desde(cuentas.alice);
+            sistema.finalizar_votacion(1);
+            assert_eq!(sistema.get_resultado(1).unwrap().conteo, vec![(cuentas.bob, 1)]);
+        }
+
+        #[ink::test]
+        fn baja_quita_candidaturas_y_bloquea_el_dni(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie]);
+            crear_votacion_con_candidatos(&mut sistema, 1, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.bob, cuentas.charlie]);
+
+            sistema.eliminar_usuario(cuentas.bob, String::from("Dni duplicado"));
+            assert_eq!(sistema.get_cant_candidatos_vot(1), 1);
+            assert_eq!(sistema.get_cant_usuarios(), 1);
+            assert_eq!(sistema.get_registro_suspensiones(None).iter().map(|r| r.accion.clone()).collect::<Vec<_>>(), vec![AccionSuspension::Baja]);
+
+            llamar_desde(cuentas.eve);
+            assert!(falla(|| sistema.registrar_usuario(String::from("Otro"), String::from("Nombre"), fecha(1, 1, 1990), 1, None)));
+        }
+
+        #[ink::test]
+        fn migrar_cuenta_no_da_un_segundo_voto(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie, cuentas.django]);
+            crear_votacion_con_candidatos(&mut sistema, 1, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.bob]);
+            en_momento(DURANTE);
+            llamar_desde(cuentas.charlie);
+            sistema.votar(1, Boleta::Simple(1));
+
+            sistema.solicitar_migracion_cuenta(cuenta(50));
+            llamar_desde(cuenta(50));
+            sistema.confirmar_migracion_cuenta(cuentas.charlie);
+            assert!(falla(|| sistema.votar(1, Boleta::Simple(1))));  // la cuenta nueva queda como que ya voto
+
+            llamar_desde(cuentas.django);  // el que migra antes de votar vota una vez con la cuenta nueva
+            sistema.solicitar_migracion_cuenta(cuenta(51));
+            llamar_desde(cuenta(51));
+            sistema.confirmar_migracion_cuenta(cuentas.django);
+            sistema.votar(1, Boleta::Simple(1));
+            assert!(falla(|| sistema.votar(1, Boleta::Simple(1))));
+            assert_eq!(sistema.get_participacion(1).0, 2);
+        }
+
+        #[ink::test]
+        fn migrar_cuenta_por_el_administrador_no_da_un_segundo_voto(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie]);
+            crear_votacion_con_candidatos(&mut sistema, 1, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.bob]);
+            en_momento(DURANTE);
+            llamar_desde(cuentas.charlie);
+            sistema.votar(1, Boleta::Simple(1));
+
+            llamar_desde(cuentas.alice);
+            assert!(falla(|| sistema.migrar_cuenta_usuario(1, cuentas.charlie, cuenta(50))));  // el dni de charlie es 2
+            assert!(falla(|| sistema.migrar_cuenta_usuario(2, cuentas.charlie, cuentas.bob)));  // la cuenta nueva ya esta en uso
+            sistema.migrar_cuenta_usuario(2, cuentas.charlie, cuenta(50));
+            llamar_desde(cuenta(50));
+            assert!(falla(|| sistema.votar(1, Boleta::Simple(1))));
+            en_momento(DESPUES_DEL_FIN);
+            llamar_desde(cuentas.alice);
+            sistema.finalizar_votacion(1);
+            assert_eq!(sistema.get_resultado(1).unwrap().conteo, vec![(cuentas.bob, 1)]);
+        }
+
+        #[ink::test]
+        fn invitacion_atada_a_la_cuenta_y_al_dni(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[]);
+            let hash = sistema.hash_codigo("bienvenida", cuentas.bob, 40);
+            sistema.publicar_invitaciones(vec![hash], None);
+            let registrar = |sistema:&mut Sistema, dni:i128| sistema.registrar_usuario(String::from("Nombre"), String::from("Apellido"), fecha(1, 1, 1980), dni, Some(String::from("bienvenida")));
+
+            llamar_desde(cuentas.charlie);
+            assert!(falla(|| registrar(&mut sistema, 40)));  // otra cuenta
+            llamar_desde(cuentas.bob);
+            assert!(falla(|| registrar(&mut sistema, 41)));  // otro dni
+            registrar(&mut sistema, 40);
+            assert_eq!(sistema.get_cant_usuarios(), 1);
+            assert_eq!(sistema.get_cant_invitaciones(), 0);
+        }
+
+        #[ink::test]
+        fn clonar_padron_de_una_votacion_abierta(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie, cuentas.django]);
+            crear_votacion_con_candidatos(&mut sistema, 1, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.bob]);
+            crear_votacion_con_candidatos(&mut sistema, 2, configuracion(ModoInscripcion::PadronCerrado, TipoVotacion::Mayoria), &[]);
+            let errores = sistema.clonar_padron(1, 2, true);
+            assert!(errores.is_empty());
+            assert_eq!(sistema.get_cant_votantes_vot(2), 2);  // bob es candidato y no puede votar
+            assert_eq!(sistema.get_cant_candidatos_vot(2), 1);
+        }
+
+        //Cambiar categorias o dar de baja despues del inicio no cambia el padron de una votacion abierta, aunque todavia no se haya congelado
+        #[ink::test]
+        fn padron_abierto_al_inicio(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie, cuentas.django, cuentas.eve]);
+            [cuentas.bob, cuentas.charlie, cuentas.django].into_iter().for_each(|acc| sistema.asignar_categoria(acc, String::from("Docentes")));
+            let mut config = configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria);
+            config.requisitos.categorias = Some(vec![String::from("Docentes")]);
+            crear_votacion_con_candidatos(&mut sistema, 1, config, &[cuentas.bob]);
+
+            en_momento(DURANTE);
+            sistema.asignar_categoria(cuentas.charlie, String::from("Estudiantes"));
+            sistema.asignar_categoria(cuentas.eve, String::from("Docentes"));
+            sistema.eliminar_usuario(cuentas.django, String::from("Baja"));
+            let mut esperado = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&vec![2i128, 3], &mut esperado);
+            assert_eq!(sistema.get_hash_padron_congelado(1), Some(esperado));
+            assert_eq!(sistema.get_participacion(1), (0, 2));
+
+            llamar_desde(cuentas.charlie);
+            sistema.votar(1, Boleta::Simple(1));
+            llamar_desde(cuentas.eve);
+            sistema.votar(1, Boleta::Simple(1));  // no esta en el padron, el voto no se cuenta
+            assert_eq!(sistema.get_participacion(1), (1, 2));
+        }
+
+        #[ink::test]
+        fn puntaje_lineal_usa_hasta_los_creditos(){
+            let mut v = votacion_de(TipoVotacion::Puntaje{ creditos:10, cuadratico:false }, 3);
+            v.sumar_boleta(Boleta::Puntajes(vec![(cuenta(1), 6), (cuenta(3), 4)]), None, 1);
+            assert_eq!(v.conteo_ordenado(), vec![(cuenta(1), 6), (cuenta(3), 4), (cuenta(2), 0)]);
+            assert!(falla(|| v.sumar_boleta(Boleta::Puntajes(vec![(cuenta(1), 6), (cuenta(2), 5)]), None, 1)));
+        }
+
+        #[ink::test]
+        fn puntaje_cuadratico_con_los_creditos_justos(){
+            let mut v = votacion_de(TipoVotacion::Puntaje{ creditos:10, cuadratico:true }, 3);
+            v.sumar_boleta(Boleta::Puntajes(vec![(cuenta(2), 3), (cuenta(1), 1)]), None, 1);  // 3*3 + 1*1 = 10
+            assert_eq!(v.conteo_ordenado(), vec![(cuenta(2), 3), (cuenta(1), 1), (cuenta(3), 0)]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "LA BOLETA USA 13 CREDITOS Y SOLO TENES 10")]
+        fn puntaje_cuadratico_pasado_de_creditos(){
+            let mut v = votacion_de(TipoVotacion::Puntaje{ creditos:10, cuadratico:true }, 3);
+            v.sumar_boleta(Boleta::Puntajes(vec![(cuenta(2), 3), (cuenta(1), 2)]), None, 1);
+        }
+
+        fn consulta(con_abstencion:bool, mayoria:Mayoria)->Consulta{
+            let mut opciones = vec![String::from("Si"), String::from("No")];
+            if con_abstencion {
+                opciones.push(String::from("Abstencion"));
+            }
+            Consulta{ pregunta:String::from("Pregunta"), opciones, con_abstencion, mayoria }
+        }
+
+        #[ink::test]
+        fn referendum_por_mayoria_simple(){
+            let c = consulta(false, Mayoria::Simple);
+            assert!(c.esta_aprobada(&[6, 4]));
+            assert!(!c.esta_aprobada(&[5, 5]));  // hace falta mas de la mitad
+        }
+
+        #[ink::test]
+        fn referendum_por_mayoria_calificada(){
+            let c = consulta(false, Mayoria::Calificada{ porcentaje:66 });
+            assert!(c.esta_aprobada(&[66, 34]));
+            assert!(!c.esta_aprobada(&[65, 35]));
+        }
+
+        #[ink::test]
+        fn referendum_sin_contar_abstenciones(){
+            assert!(consulta(true, Mayoria::Simple).esta_aprobada(&[5, 4, 100]));
+            assert!(consulta(true, Mayoria::Calificada{ porcentaje:66 }).esta_aprobada(&[2, 1, 10]));  // 2 de 3
+            assert!(!consulta(true, Mayoria::Calificada{ porcentaje:66 }).esta_aprobada(&[3, 2, 0]));
+        }
+
+        #[ink::test]
+        fn referendum_sin_base_no_se_aprueba(){
+            assert!(!consulta(true, Mayoria::Simple).esta_aprobada(&[0, 0, 7]));
+            assert!(!consulta(false, Mayoria::Calificada{ porcentaje:51 }).esta_aprobada(&[0, 0]));
+        }
+
+        #[ink::test]
+        fn grupo_con_otros_requisitos_de_votante(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie]);
+            let mut mayores = configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria);
+            mayores.requisitos.edad_minima_votante = 30;
+            crear_votacion_con_candidatos(&mut sistema, 1, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.bob]);
+            crear_votacion_con_candidatos(&mut sistema, 2, mayores, &[cuentas.charlie]);
+            crear_votacion_con_candidatos(&mut sistema, 3, configuracion(ModoInscripcion::PadronCerrado, TipoVotacion::Mayoria), &[]);
+            assert!(falla(|| sistema.crear_grupo_boletas(1, vec![1, 2])));
+            assert!(falla(|| sistema.crear_grupo_boletas(1, vec![1, 3])));
+            assert_eq!(sistema.get_grupo_boletas(1), None);
+        }
+
+        #[ink::test]
+        fn votar_grupo_es_todo_o_nada(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie, cuentas.django]);
+            crear_votacion_con_candidatos(&mut sistema, 1, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.bob]);
+            crear_votacion_con_candidatos(&mut sistema, 2, configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria), &[cuentas.charlie]);
+            sistema.crear_grupo_boletas(1, vec![1, 2]);
+            en_momento(DURANTE);
+            llamar_desde(cuentas.django);
+            assert!(falla(|| sistema.votar_grupo(1, vec![(1, Boleta::Simple(1)), (2, Boleta::Simple(5))])));  // la segunda boleta es invalida
+            assert_eq!(sistema.get_participacion(1).0, 0);
+            assert_eq!(sistema.get_participacion(2).0, 0);
+            sistema.votar_grupo(1, vec![(1, Boleta::Simple(1)), (2, Boleta::Simple(1))]);
+            assert_eq!(sistema.get_participacion(1).0, 1);
+            assert_eq!(sistema.get_participacion(2).0, 1);
+        }
+
+        #[ink::test]
+        fn claustros_con_las_categorias_habilitadas(){
+            let mut sistema = sistema_con_usuarios(&[]);
+            let mut config = configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria);
+            config.claustros = Some(vec![
+                Claustro{ categoria:String::from("Docentes"), peso:60 },
+                Claustro{ categoria:String::from("Estudiantes"), peso:40 },
+            ]);
+            assert!(falla(|| sistema.crear_votacion(1, String::from("Puesto"), fecha(1, 1, 2021), fecha(5, 1, 2021), config.clone())));  // sin categorias podria votar cualquiera
+            config.requisitos.categorias = Some(vec![String::from("Docentes")]);
+            assert!(falla(|| sistema.crear_votacion(1, String::from("Puesto"), fecha(1, 1, 2021), fecha(5, 1, 2021), config.clone())));
+            config.requisitos.categorias = Some(vec![String::from("Docentes"), String::from("Estudiantes"), String::from("Nodocentes")]);
+            assert!(falla(|| sistema.crear_votacion(1, String::from("Puesto"), fecha(1, 1, 2021), fecha(5, 1, 2021), config.clone())));
+            config.requisitos.categorias = Some(vec![String::from("Estudiantes"), String::from("Docentes")]);
+            sistema.crear_votacion(1, String::from("Puesto"), fecha(1, 1, 2021), fecha(5, 1, 2021), config);
+        }
+
+        fn compromiso(id_de_votacion:i32, votante:AccountId, boleta:Boleta, sal:[u8; 32])->[u8; 32]{
+            let contrato = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            let mut hash = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&(contrato, id_de_votacion, votante, boleta, sal), &mut hash);
+            hash
+        }
+
+        #[ink::test]
+        fn compromiso_copiado_no_se_puede_revelar(){
+            let cuentas = cuentas_de_prueba();
+            let mut sistema = sistema_con_usuarios(&[cuentas.bob, cuentas.charlie, cuentas.django]);
+            let mut config = configuracion(ModoInscripcion::Abierta, TipoVotacion::Mayoria);
+            config.dias_revelacion = Some(2);
+            crear_votacion_con_candidatos(&mut sistema, 1, config, &[cuentas.bob]);
+            let sal = [7u8; 32];
+            let hash = compromiso(1, cuentas.charlie, Boleta::Simple(1), sal);
+            en_momento(DURANTE);
+            llamar_desde(cuentas.charlie);
+            sistema.votar_secreto(1, hash);
+            llamar_desde(cuentas.django);
+            sistema.votar_secreto(1, hash);  // copia el compromiso de charlie sin saber que voto
+            en_momento(DESPUES_DEL_FIN);
+            assert!(falla(|| sistema.revelar_voto(1, Boleta::Simple(1), sal)));
+            llamar_desde(cuentas.charlie);
+            sistema.revelar_voto(1, Boleta::Simple(1), sal);
+        }
+
+        #[ink::test]
+        fn suspender_antes_del_inicio_borra_la_clave_anonima(){
+            let cuentas = cuentas_de_prueba();
+            let (mut sistema, privadas) = votacion_anonima_con_claves(&[cuentas.charlie, cuentas.django]);
+            sistema.suspender_usuario(cuentas.django, String::from("Registro fraudulento"));
+            assert_eq!(sistema.get_claves_anonimas(1), vec![clave_publica(&privadas[0])]);
+            sistema.eliminar_usuario(cuentas.charlie, String::from("Registro fraudulento"));
+            assert!(sistema.get_claves_anonimas(1).is_empty());
+        }
+
+        #[ink::test]
+        fn clave_de_un_suspendido_no_entra_en_el_anillo(){
+            let cuentas = cuentas_de_prueba();
+            let (mut sistema, privadas) = votacion_anonima_con_claves(&[cuentas.charlie, cuentas.django, cuentas.eve]);
+            let anillo = sistema.get_claves_anonimas(1);
+            en_momento(DURANTE);
+            sistema.suspender_usuario(cuentas.django, String::from("Registro fraudulento"));
+            let habilitadas = sistema.get_claves_anonimas(1);
+            assert_eq!(habilitadas.len(), 2);
+            assert!(!habilitadas.contains(&clave_publica(&privadas[1])));
+
+            llamar_desde(cuentas.frank);
+            let mensaje = sistema.mensaje_anonimo(1, &Boleta::Simple(1));
+            let pos = anillo.iter().position(|c| *c == clave_publica(&privadas[1])).unwrap();
+            assert!(falla(|| sistema.votar_anonimo(1, Boleta::Simple(1), firmar_anillo(mensaje, privadas[1], anillo.clone(), pos))));  // la clave del suspendido ya no vale
+            let pos = anillo.iter().position(|c| *c == clave_publica(&privadas[0])).unwrap();
+            assert!(falla(|| sistema.votar_anonimo(1, Boleta::Simple(1), firmar_anillo(mensaje, privadas[0], anillo.clone(), pos))));  // ni sirve para esconder a otro
+            let pos = habilitadas.iter().position(|c| *c == clave_publica(&privadas[0])).unwrap();
+            sistema.votar_anonimo(1, Boleta::Simple(1), firmar_anillo(mensaje, privadas[0], habilitadas, pos));
+            assert_eq!(sistema.get_participacion(1).0, 1);
+        }
+    }
 }
@@ -0,0 +1,61 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+//Oraculo de identidad de prueba para usar con Sistema en una red local. El dueño carga a mano que dni pertenece a que cuenta
+#[ink::contract]
+mod oraculo_mock {
+    use ink::storage::Mapping;
+    use ink::env::hash::Blake2x256;
+    use sistema::OraculoIdentidad;
+
+    #[ink(storage)]
+    pub struct OraculoMock {
+        atestaciones: Mapping<[u8; 32], AccountId>,  // hash del dni -> cuenta
+        owner: AccountId,
+    }
+
+    impl OraculoMock {
+
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self { atestaciones: Mapping::default(), owner: Self::env().caller() }
+        }
+
+        //Unicamente el dueño puede dar fe de que un dni pertenece a una cuenta
+        #[ink(message)]
+        pub fn atestar(&mut self, dni: i128, cuenta: AccountId) {
+            if self.env().caller() != self.owner {
+                panic!("SOLO EL DUEÑO PUEDE CARGAR ATESTACIONES");
+            }
+            let hash = self.hash_dni(dni);
+            self.atestaciones.insert(hash, &cuenta);
+        }
+
+        #[ink(message)]
+        pub fn revocar(&mut self, dni: i128) {
+            if self.env().caller() != self.owner {
+                panic!("SOLO EL DUEÑO PUEDE REVOCAR ATESTACIONES");
+            }
+            let hash = self.hash_dni(dni);
+            self.atestaciones.remove(hash);
+        }
+
+        //Mismo hash que usa Sistema para consultar al oraculo
+        #[ink(message)]
+        pub fn hash_dni(&self, dni: i128) -> [u8; 32] {
+            self.env().hash_encoded::<Blake2x256, _>(&dni)
+        }
+    }
+
+    impl Default for OraculoMock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl OraculoIdentidad for OraculoMock {
+        #[ink(message)]
+        fn verificar_dni(&self, dni_hash: [u8; 32], cuenta: AccountId) -> bool {
+            self.atestaciones.get(dni_hash) == Some(cuenta)
+        }
+    }
+}